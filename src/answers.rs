@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use toml::{Table, Value};
+
+use crate::runner::{Outcome, Status};
+use crate::solver::Part;
+
+/// Known-good answers, stored as TOML keyed by day and part:
+///
+/// ```toml
+/// [day05]
+/// part1 = "143"
+/// part2 = "123"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    days: BTreeMap<u8, BTreeMap<Part, String>>,
+}
+
+fn part_key(part: Part) -> String {
+    format!("part{}", part)
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let mut days = BTreeMap::new();
+        for (day_key, parts) in table {
+            let day: u8 = day_key
+                .strip_prefix("day")
+                .unwrap_or(&day_key)
+                .parse()
+                .map_err(|_| anyhow!("invalid day key [{}]", day_key))?;
+            let Value::Table(parts) = parts else {
+                bail!("[{}] should be a table of part1/part2", day_key);
+            };
+            let mut answers = BTreeMap::new();
+            for (part_str, value) in parts {
+                let part = match part_str.as_str() {
+                    "part1" => Part::One,
+                    "part2" => Part::Two,
+                    _ => bail!("invalid key {} in [{}]", part_str, day_key),
+                };
+                // Allow bare integers as well as strings
+                let answer = match value {
+                    Value::String(s) => s,
+                    Value::Integer(i) => i.to_string(),
+                    v => bail!("invalid answer {} for {}.{}", v, day_key, part_str),
+                };
+                answers.insert(part, answer);
+            }
+            days.insert(day, answers);
+        }
+        Ok(Self { days })
+    }
+
+    /// Loads answers from `path`; a missing file has no answers yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Option<&str> {
+        self.days.get(&day)?.get(&part).map(|s| s.as_str())
+    }
+
+    /// Locks in an answer, unless one is already recorded. Returns whether
+    /// the answer was added.
+    pub fn record(&mut self, day: u8, part: Part, answer: &str) -> bool {
+        let parts = self.days.entry(day).or_default();
+        if parts.contains_key(&part) {
+            return false;
+        }
+        parts.insert(part, answer.to_owned());
+        true
+    }
+}
+
+impl std::fmt::Display for Answers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut table = Table::new();
+        for (day, parts) in &self.days {
+            let mut t = Table::new();
+            for (part, answer) in parts {
+                t.insert(part_key(*part), Value::String(answer.clone()));
+            }
+            table.insert(format!("day{:02}", day), Value::Table(t));
+        }
+        write!(f, "{}", table)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Match,
+    Mismatch {
+        expected: String,
+        got: String,
+    },
+    /// No answer locked in for this part, and nothing went wrong
+    Unknown,
+    /// The solver didn't produce an answer where one was locked in, e.g.
+    /// missing input, or it failed whether or not one was
+    NoAnswer(Status),
+}
+
+impl Verdict {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Self::Mismatch { .. } | Self::NoAnswer(_))
+    }
+}
+
+pub fn verify(outcome: &Outcome, answers: &Answers) -> Verdict {
+    let expected = answers.get(outcome.day, outcome.part);
+    match (&outcome.answer, expected) {
+        (Some(got), Some(expected)) if got == expected => Verdict::Match,
+        (Some(got), Some(expected)) => Verdict::Mismatch {
+            expected: expected.to_owned(),
+            got: got.clone(),
+        },
+        (Some(_), None) => Verdict::Unknown,
+        (None, None) if !matches!(outcome.status, Status::Failed(_)) => Verdict::Unknown,
+        (None, _) => Verdict::NoAnswer(outcome.status.clone()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use indoc::indoc;
+
+    use super::*;
+
+    fn outcome(day: u8, part: Part, answer: Option<&str>) -> Outcome {
+        Outcome {
+            day,
+            part,
+            answer: answer.map(|a| a.to_owned()),
+            elapsed: Duration::ZERO,
+            status: if answer.is_some() {
+                Status::Ok
+            } else {
+                Status::Failed("oops".to_owned())
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let answers = Answers::parse(indoc! {r#"
+            [day05]
+            part1 = "143"
+            part2 = 123
+
+            [17]
+            part1 = "4,6,3,5,6,3,5,2,1,0"
+        "#})?;
+        assert_eq!(answers.get(5, Part::One), Some("143"));
+        assert_eq!(answers.get(5, Part::Two), Some("123"));
+        assert_eq!(answers.get(17, Part::One), Some("4,6,3,5,6,3,5,2,1,0"));
+        assert_eq!(answers.get(17, Part::Two), None);
+        assert_eq!(answers.get(1, Part::One), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert!(Answers::parse("[dayfive]\npart1 = \"1\"").is_err());
+        assert!(Answers::parse("[day05]\npart3 = \"1\"").is_err());
+        assert!(Answers::parse("[day05]\npart1 = 1.5").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut answers = Answers::default();
+        assert!(answers.record(5, Part::One, "143"));
+        assert!(answers.record(5, Part::Two, "123"));
+        assert!(!answers.record(5, Part::Two, "999"));
+        assert_eq!(answers.get(5, Part::Two), Some("123"));
+        let text = answers.to_string();
+        assert_eq!(Answers::parse(&text)?, answers);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verify() -> Result<()> {
+        let answers = Answers::parse("[day05]\npart1 = \"143\"\npart2 = \"123\"")?;
+        assert_eq!(
+            verify(&outcome(5, Part::One, Some("143")), &answers),
+            Verdict::Match
+        );
+        let v = verify(&outcome(5, Part::Two, Some("124")), &answers);
+        assert!(v.is_failure());
+        assert_eq!(
+            v,
+            Verdict::Mismatch {
+                expected: "123".to_owned(),
+                got: "124".to_owned()
+            }
+        );
+        assert!(verify(&outcome(5, Part::Two, None), &answers).is_failure());
+        assert_eq!(
+            verify(&outcome(6, Part::One, Some("41")), &answers),
+            Verdict::Unknown
+        );
+        // A failure counts even with nothing locked in to compare against
+        assert!(verify(&outcome(6, Part::One, None), &answers).is_failure());
+
+        // Missing input or an unsolved part only matters with an answer
+        // locked in, which it no longer checks
+        for status in [Status::NoInput, Status::NotSolved] {
+            let mut o = outcome(5, Part::One, None);
+            o.status = status.clone();
+            assert_eq!(verify(&o, &answers), Verdict::NoAnswer(status));
+            assert!(verify(&o, &answers).is_failure());
+            o.day = 6;
+            assert_eq!(verify(&o, &answers), Verdict::Unknown);
+        }
+        Ok(())
+    }
+}
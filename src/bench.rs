@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use toml::{Table, Value};
+
+use crate::common::Input;
+use crate::solver::{take_parse_time, Params, Part, Solver};
+
+/// A median this much slower than the saved run is reported as a regression
+pub const REGRESSION_THRESHOLD: f64 = 0.10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Result<Self> {
+        if samples.is_empty() {
+            bail!("no samples");
+        }
+        samples.sort();
+        Ok(Self {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        })
+    }
+
+    fn to_table(self) -> Table {
+        let mut t = Table::new();
+        for (key, d) in [
+            ("min_ns", self.min),
+            ("median_ns", self.median),
+            ("max_ns", self.max),
+        ] {
+            t.insert(key.to_owned(), Value::Integer(d.as_nanos() as i64));
+        }
+        t
+    }
+
+    fn from_table(t: &Table) -> Result<Self> {
+        let get = |key: &str| -> Result<Duration> {
+            let ns = t
+                .get(key)
+                .and_then(|v| v.as_integer())
+                .ok_or_else(|| anyhow!("missing {}", key))?;
+            Ok(Duration::from_nanos(ns.try_into()?))
+        };
+        Ok(Self {
+            min: get("min_ns")?,
+            median: get("median_ns")?,
+            max: get("max_ns")?,
+        })
+    }
+}
+
+/// Timings for one day/part. `load` is reading and normalising the input
+/// file, `parse` is the day turning it into its own types, and `solve` is
+/// the rest of the part function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchResult {
+    pub iterations: usize,
+    pub load: Stats,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl BenchResult {
+    /// Relative change in median solve time against `baseline`, e.g. `0.25`
+    /// for 25% slower
+    pub fn change(&self, baseline: &Self) -> f64 {
+        let new = self.solve.median.as_secs_f64();
+        let old = baseline.solve.median.as_secs_f64();
+        if old == 0. {
+            // Too quick to measure before, so any time at all is a change
+            return if new == 0. { 0. } else { f64::INFINITY };
+        }
+        (new - old) / old
+    }
+
+    pub fn is_regression(&self, baseline: &Self) -> bool {
+        self.change(baseline) > REGRESSION_THRESHOLD
+    }
+}
+
+pub fn bench(
+    solver: &dyn Solver,
+    part: Part,
+    input: &Path,
+    params: &Params,
+    warmup: usize,
+    iterations: usize,
+) -> Result<BenchResult> {
+    for _ in 0..warmup {
+        solver.solve(part, &Input::read(input)?, params)?;
+    }
+    let mut load = Vec::with_capacity(iterations);
+    let mut parse = Vec::with_capacity(iterations);
+    let mut solve = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        let text = Input::read(input)?;
+        load.push(start.elapsed());
+
+        take_parse_time();
+        let start = Instant::now();
+        solver.solve(part, &text, params)?;
+        let elapsed = start.elapsed();
+        let parsing = take_parse_time();
+        parse.push(parsing);
+        solve.push(elapsed.saturating_sub(parsing));
+    }
+    Ok(BenchResult {
+        iterations,
+        load: Stats::from_samples(load)?,
+        parse: Stats::from_samples(parse)?,
+        solve: Stats::from_samples(solve)?,
+    })
+}
+
+/// Saved benchmark results, keyed by day and part like the answers file.
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    table: Table,
+}
+
+fn keys(day: u8, part: Part) -> (String, String) {
+    (format!("day{:02}", day), format!("part{}", part))
+}
+
+impl History {
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let table = fs::read_to_string(path)?.parse()?;
+        Ok(Self { table })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.table.to_string())?;
+        Ok(())
+    }
+
+    pub fn get(&self, day: u8, part: Part) -> Result<Option<BenchResult>> {
+        let (day_key, part_key) = keys(day, part);
+        let Some(t) = self
+            .table
+            .get(&day_key)
+            .and_then(|v| v.get(&part_key))
+            .and_then(|v| v.as_table())
+        else {
+            return Ok(None);
+        };
+        if !t.contains_key("parse") {
+            // Saved before parsing was timed separately, when `solve`
+            // included it, so there's nothing comparable
+            return Ok(None);
+        }
+        let stats = |key: &str| -> Result<Stats> {
+            let st = t
+                .get(key)
+                .and_then(|v| v.as_table())
+                .ok_or_else(|| anyhow!("{}.{} missing {}", day_key, part_key, key))?;
+            Stats::from_table(st)
+        };
+        let iterations = t
+            .get("iterations")
+            .and_then(|v| v.as_integer())
+            .unwrap_or(0)
+            .try_into()?;
+        Ok(Some(BenchResult {
+            iterations,
+            load: stats("load")?,
+            parse: stats("parse")?,
+            solve: stats("solve")?,
+        }))
+    }
+
+    pub fn insert(&mut self, day: u8, part: Part, result: &BenchResult) {
+        let (day_key, part_key) = keys(day, part);
+        let mut t = Table::new();
+        t.insert(
+            "iterations".to_owned(),
+            Value::Integer(result.iterations as i64),
+        );
+        t.insert("load".to_owned(), Value::Table(result.load.to_table()));
+        t.insert("parse".to_owned(), Value::Table(result.parse.to_table()));
+        t.insert("solve".to_owned(), Value::Table(result.solve.to_table()));
+        let day_t = self
+            .table
+            .entry(day_key)
+            .or_insert_with(|| Value::Table(Table::new()));
+        if let Value::Table(day_t) = day_t {
+            day_t.insert(part_key, Value::Table(t));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    fn result(median: u64) -> BenchResult {
+        let stats = Stats {
+            min: ms(median - 1),
+            median: ms(median),
+            max: ms(median + 1),
+        };
+        BenchResult {
+            iterations: 3,
+            load: stats,
+            parse: stats,
+            solve: stats,
+        }
+    }
+
+    #[test]
+    fn test_stats() -> Result<()> {
+        let stats = Stats::from_samples(vec![ms(5), ms(1), ms(9), ms(3), ms(4)])?;
+        assert_eq!(
+            stats,
+            Stats {
+                min: ms(1),
+                median: ms(4),
+                max: ms(9)
+            }
+        );
+        assert!(Stats::from_samples(vec![]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_regression() {
+        let baseline = result(100);
+        assert!(!result(105).is_regression(&baseline));
+        assert!(result(120).is_regression(&baseline));
+        assert!(!result(50).is_regression(&baseline));
+
+        let mut instant = result(1);
+        instant.solve.median = Duration::ZERO;
+        assert_eq!(instant.change(&instant), 0.);
+        assert!(result(1).is_regression(&instant));
+    }
+
+    #[test]
+    fn test_history_round_trip() -> Result<()> {
+        let mut history = History::default();
+        history.insert(22, Part::Two, &result(100));
+        history.insert(22, Part::One, &result(10));
+        let reloaded = History {
+            table: history.table.to_string().parse()?,
+        };
+        assert_eq!(reloaded.get(22, Part::Two)?, Some(result(100)));
+        assert_eq!(reloaded.get(22, Part::One)?, Some(result(10)));
+        assert_eq!(reloaded.get(6, Part::One)?, None);
+        Ok(())
+    }
+}
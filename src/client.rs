@@ -0,0 +1,110 @@
+//! Talks to the puzzle site. Everything per-user needs the `session` cookie
+//! from a logged-in browser, see `Config`.
+
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use log::info;
+
+use crate::config::Config;
+use crate::solver::Part;
+use crate::submit::Reply;
+
+const USER_AGENT: &str = concat!("aoc/", env!("CARGO_PKG_VERSION"), " (rust cli)");
+
+pub struct Client {
+    agent: ureq::Agent,
+    base_url: String,
+    session: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: Option<&str>) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .user_agent(USER_AGENT)
+            .build();
+        Self {
+            agent,
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            session: session.map(str::to_owned),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::new(&config.base_url, config.session.as_deref())
+    }
+
+    fn session(&self) -> Result<&str> {
+        self.session.as_deref().ok_or_else(|| {
+            anyhow!("no session token: set AOC_SESSION or `session` in aoc.toml to the site's session cookie")
+        })
+    }
+
+    fn url(&self, year: u16, day: u8, path: &str) -> String {
+        format!("{}/{}/day/{}{}", self.base_url, year, day, path)
+    }
+
+    /// Downloads a day's puzzle input
+    pub fn input(&self, year: u16, day: u8) -> Result<String> {
+        let url = self.url(year, day, "/input");
+        info!("fetching {}", url);
+        let response = self
+            .agent
+            .get(&url)
+            .set("Cookie", &format!("session={}", self.session()?))
+            .call();
+        match response {
+            Ok(response) => response
+                .into_string()
+                .with_context(|| format!("reading {}", url)),
+            Err(ureq::Error::Status(404, _)) => {
+                Err(anyhow!("day {} of {} is not unlocked yet", day, year))
+            }
+            Err(e) => Err(status_error(e, &url)),
+        }
+    }
+
+    /// Posts an answer and reads the site's verdict
+    pub fn submit(&self, year: u16, day: u8, part: Part, answer: &str) -> Result<Reply> {
+        let url = self.url(year, day, "/answer");
+        info!("submitting {} to {}", answer, url);
+        let response = self
+            .agent
+            .post(&url)
+            .set("Cookie", &format!("session={}", self.session()?))
+            .send_form(&[("level", &part.to_string()), ("answer", answer)]);
+        match response {
+            Ok(response) => {
+                let html = response
+                    .into_string()
+                    .with_context(|| format!("reading {}", url))?;
+                Reply::parse(&html)
+            }
+            Err(ureq::Error::Status(404, _)) => {
+                Err(anyhow!("day {} of {} is not unlocked yet", day, year))
+            }
+            Err(e) => Err(status_error(e, &url)),
+        }
+    }
+}
+
+/// Explains a failed request, singling out a rejected session
+fn status_error(e: ureq::Error, url: &str) -> anyhow::Error {
+    match e {
+        ureq::Error::Status(code @ (400 | 401 | 403), _) => anyhow!(
+            "session token was rejected (HTTP {}): log in again and update AOC_SESSION or aoc.toml",
+            code
+        ),
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            anyhow!(
+                "HTTP {} from {}: {}",
+                code,
+                url,
+                body.lines().next().unwrap_or("")
+            )
+        }
+        e => anyhow!(e).context(format!("requesting {}", url)),
+    }
+}
@@ -1,12 +1,20 @@
-use anyhow::anyhow;
+mod grid;
+mod input;
+mod parse;
+pub mod search;
+
 use enum_iterator::Sequence;
-use num::{Num, Zero};
+use num::{Num, Signed, Zero};
 use std::{
     fmt::Display,
     iter,
     ops::{Add, Mul, Sub},
 };
 
+pub use grid::{Grid, Render};
+pub use input::{Input, Line};
+pub use parse::ParseError;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Pos<T>
 where
@@ -124,6 +132,22 @@ where
         }
     }
 
+    pub fn go8(&self, dir: Dir8) -> Self {
+        dir.steps().iter().fold(*self, |p, d| p.go(*d))
+    }
+
+    pub fn go8_bounded(&self, dir: Dir8, bounds: &Self) -> Option<Self> {
+        dir.steps()
+            .iter()
+            .try_fold(*self, |p, d| p.go_bounded(*d, bounds))
+    }
+
+    /// The in-bounds king-move neighbours, clockwise from up
+    pub fn neighbours8(self, bounds: Self) -> impl Iterator<Item = (Dir8, Self)> {
+        enum_iterator::all::<Dir8>()
+            .filter_map(move |dir| Some((dir, self.go8_bounded(dir, &bounds)?)))
+    }
+
     pub fn in_bounds(&self, bounds: &Self) -> bool {
         self.x >= T::zero() && self.x < bounds.x && self.y >= T::zero() && self.y < bounds.y
     }
@@ -139,7 +163,7 @@ where
                 return None;
             }
 
-            let out = p.clone();
+            let out = p;
 
             if p.x < self.x {
                 p.x = p.x + T::one();
@@ -175,7 +199,7 @@ where
         } else {
             other.y - self.y
         };
-        return x + y;
+        x + y
     }
 }
 
@@ -240,28 +264,150 @@ pub enum Dir {
     Right,
 }
 impl Dir {
-    // pub fn cw(&self) -> Self {
-    //     match self {
-    //         Self::Up => Self::Right,
-    //         Self::Right => Self::Down,
-    //         Self::Down => Self::Left,
-    //         Self::Left => Self::Up,
-    //     }
-    // }
+    pub fn cw(&self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Right => Self::Down,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+        }
+    }
+
+    pub fn ccw(&self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Left => Self::Down,
+            Self::Down => Self::Right,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub fn reverse(&self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Left => Self::Right,
+            Self::Down => Self::Up,
+            Self::Right => Self::Left,
+        }
+    }
 
     /// Orthogonal directions, CCW then CW
     pub fn orthos(&self) -> (Self, Self) {
+        (self.ccw(), self.cw())
+    }
+
+    /// Parses an arrow as drawn in puzzle inputs: `^`, `<`, `v` or `>`
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Self::Up),
+            '<' => Some(Self::Left),
+            'v' => Some(Self::Down),
+            '>' => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(&self) -> char {
         match self {
-            Dir::Up => (Dir::Left, Dir::Right),
-            Dir::Left => (Dir::Down, Dir::Up),
-            Dir::Down => (Dir::Right, Dir::Left),
-            Dir::Right => (Dir::Up, Dir::Down),
+            Self::Up => '^',
+            Self::Left => '<',
+            Self::Down => 'v',
+            Self::Right => '>',
         }
     }
+
+    /// Unit step in this direction, with y growing downwards
+    pub fn as_delta<T>(&self) -> Pos<T>
+    where
+        T: Copy + Num + Display + Signed,
+    {
+        let (x, y) = match self {
+            Self::Up => (T::zero(), -T::one()),
+            Self::Left => (-T::one(), T::zero()),
+            Self::Down => (T::zero(), T::one()),
+            Self::Right => (T::one(), T::zero()),
+        };
+        Pos { x, y }
+    }
 }
 
-pub fn graphrs_anyhow(err: graphrs::Error) -> anyhow::Error {
-    anyhow!("graphrs: {}", err)
+/// Eight-way directions, clockwise from up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Sequence)]
+pub enum Dir8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+impl Dir8 {
+    const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// Turns clockwise by `eighths` of a full turn; negative turns CCW
+    pub fn rotate(&self, eighths: isize) -> Self {
+        let i = (*self as isize + eighths).rem_euclid(8);
+        Self::ALL[i as usize]
+    }
+
+    pub fn cw45(&self) -> Self {
+        self.rotate(1)
+    }
+
+    pub fn ccw45(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    pub fn cw90(&self) -> Self {
+        self.rotate(2)
+    }
+
+    pub fn ccw90(&self) -> Self {
+        self.rotate(-2)
+    }
+
+    pub fn opposite(&self) -> Self {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        self.steps().len() == 2
+    }
+
+    /// The orthogonal steps making up this direction
+    pub fn steps(&self) -> &'static [Dir] {
+        match self {
+            Self::Up => &[Dir::Up],
+            Self::UpRight => &[Dir::Up, Dir::Right],
+            Self::Right => &[Dir::Right],
+            Self::DownRight => &[Dir::Down, Dir::Right],
+            Self::Down => &[Dir::Down],
+            Self::DownLeft => &[Dir::Down, Dir::Left],
+            Self::Left => &[Dir::Left],
+            Self::UpLeft => &[Dir::Up, Dir::Left],
+        }
+    }
+}
+impl From<Dir> for Dir8 {
+    fn from(dir: Dir) -> Self {
+        match dir {
+            Dir::Up => Self::Up,
+            Dir::Left => Self::Left,
+            Dir::Down => Self::Down,
+            Dir::Right => Self::Right,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -274,8 +420,7 @@ mod test {
     fn test_pos_generator_unsigned() -> Result<()> {
         let p: Pos<usize> = Pos { x: 2, y: 3 };
         let expect: Vec<Pos<usize>> = (0..3_usize)
-            .map(|y| (0..2_usize).map(move |x| Pos { x, y }))
-            .flatten()
+            .flat_map(|y| (0..2_usize).map(move |x| Pos { x, y }))
             .collect();
         let generated: Vec<Pos<usize>> = p.generator().collect();
         assert_eq!(generated, expect);
@@ -308,8 +453,7 @@ mod test {
     fn test_pos_generator_isize_okay() -> Result<()> {
         let p: Pos<isize> = Pos { x: 2, y: 3 };
         let expect: Vec<Pos<isize>> = (0..3_isize)
-            .map(|y| (0..2_isize).map(move |x| Pos { x, y }))
-            .flatten()
+            .flat_map(|y| (0..2_isize).map(move |x| Pos { x, y }))
             .collect();
         let generated: Vec<Pos<isize>> = p.generator().collect();
         assert_eq!(generated, expect);
@@ -325,6 +469,87 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_dir_rotate() {
+        for dir in enum_iterator::all::<Dir>() {
+            assert_eq!(dir.cw().ccw(), dir);
+            assert_eq!(dir.cw().cw(), dir.reverse());
+            assert_eq!(dir.ccw().ccw(), dir.reverse());
+            assert_eq!(dir.orthos(), (dir.ccw(), dir.cw()));
+        }
+        assert_eq!(Dir::Up.cw(), Dir::Right);
+        assert_eq!(Dir::Up.ccw(), Dir::Left);
+    }
+
+    #[test]
+    fn test_dir_chars() {
+        for dir in enum_iterator::all::<Dir>() {
+            assert_eq!(Dir::from_char(dir.to_char()), Some(dir));
+        }
+        assert_eq!(Dir::from_char('v'), Some(Dir::Down));
+        assert_eq!(Dir::from_char('x'), None);
+    }
+
+    #[test]
+    fn test_dir_as_delta() {
+        let p: Pos<isize> = Pos { x: 3, y: 5 };
+        for dir in enum_iterator::all::<Dir>() {
+            assert_eq!(p + dir.as_delta(), p.go(dir));
+        }
+        assert_eq!(Dir::Up.as_delta::<i64>(), Pos { x: 0, y: -1 });
+    }
+
+    #[test]
+    fn test_dir8_rotate() {
+        assert_eq!(Dir8::Up.cw45(), Dir8::UpRight);
+        assert_eq!(Dir8::Up.ccw45(), Dir8::UpLeft);
+        assert_eq!(Dir8::UpLeft.cw90(), Dir8::UpRight);
+        assert_eq!(Dir8::Right.ccw90(), Dir8::Up);
+        assert_eq!(Dir8::DownLeft.opposite(), Dir8::UpRight);
+        assert_eq!(Dir8::Left.rotate(-9), Dir8::DownLeft);
+        for dir in enum_iterator::all::<Dir8>() {
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.cw45().ccw45(), dir);
+            assert_eq!(dir.cw90().cw90(), dir.opposite());
+        }
+        assert_eq!(Dir8::from(Dir::Down), Dir8::Down);
+        assert!(Dir8::DownRight.is_diagonal());
+        assert!(!Dir8::Down.is_diagonal());
+    }
+
+    #[test]
+    fn test_go8() {
+        let p: Pos<isize> = Pos { x: 1, y: 1 };
+        assert_eq!(p.go8(Dir8::UpLeft), Pos { x: 0, y: 0 });
+        assert_eq!(p.go8(Dir8::DownRight), Pos { x: 2, y: 2 });
+        let bounds = Pos { x: 2, y: 3 };
+        assert_eq!(
+            p.go8_bounded(Dir8::UpLeft, &bounds),
+            Some(Pos { x: 0, y: 0 })
+        );
+        assert_eq!(p.go8_bounded(Dir8::UpRight, &bounds), None);
+        let origin: Pos<usize> = Pos { x: 0, y: 0 };
+        assert_eq!(
+            origin.go8_bounded(Dir8::DownLeft, &Pos { x: 2, y: 2 }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_neighbours8() {
+        let bounds: Pos<usize> = Pos { x: 3, y: 3 };
+        assert_eq!(Pos { x: 1, y: 1 }.neighbours8(bounds).count(), 8);
+        let corner: Vec<_> = Pos { x: 0, y: 0 }.neighbours8(bounds).collect();
+        assert_eq!(
+            corner,
+            vec![
+                (Dir8::Right, Pos { x: 1, y: 0 }),
+                (Dir8::DownRight, Pos { x: 1, y: 1 }),
+                (Dir8::Down, Pos { x: 0, y: 1 }),
+            ]
+        );
+    }
+
     #[test]
     fn test_manhattan() -> Result<()> {
         let p1: Pos<usize> = Pos { x: 1, y: 3 };
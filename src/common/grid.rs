@@ -0,0 +1,250 @@
+use std::fmt::{Display, Write};
+use std::ops::{Index, IndexMut};
+
+use anyhow::{bail, Result};
+
+use super::{Dir, Dir8, Pos};
+
+/// How a tile is drawn when its grid is displayed, and what `Grid::find`
+/// matches against.
+pub trait Render {
+    fn render(&self) -> char;
+}
+
+impl Render for char {
+    fn render(&self) -> char {
+        *self
+    }
+}
+
+/// A rectangular map of tiles, indexed by `Pos<usize>` with `(0,0)` top left.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    bounds: Pos<usize>,
+    tiles: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A grid of `bounds.x` by `bounds.y` tiles, all `fill`
+    pub fn new(bounds: Pos<usize>, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            bounds,
+            tiles: vec![fill; bounds.x * bounds.y],
+        }
+    }
+
+    /// A grid from its tiles row by row, which must number `bounds.x` by
+    /// `bounds.y`
+    pub(super) fn from_tiles(bounds: Pos<usize>, tiles: Vec<T>) -> Self {
+        debug_assert_eq!(tiles.len(), bounds.x * bounds.y);
+        Self { bounds, tiles }
+    }
+
+    /// Parses one row per line, turning each char into a tile with `tile`.
+    /// Blank lines are skipped, and all rows must be the same width.
+    pub fn from_lines<I, S, F>(lines: I, mut tile: F) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+        F: FnMut(char) -> Result<T>,
+    {
+        let mut tiles = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in lines {
+            let line = line.as_ref();
+            if line.is_empty() {
+                continue;
+            }
+            let before = tiles.len();
+            for c in line.chars() {
+                tiles.push(tile(c)?);
+            }
+            let w = tiles.len() - before;
+            match width {
+                None => width = Some(w),
+                Some(width) if width != w => {
+                    bail!("row {} is {} wide, expected {}", height, w, width)
+                }
+                _ => {}
+            }
+            height += 1;
+        }
+        let Some(width) = width else {
+            bail!("empty grid");
+        };
+        Ok(Self {
+            bounds: Pos {
+                x: width,
+                y: height,
+            },
+            tiles,
+        })
+    }
+
+    pub fn bounds(&self) -> Pos<usize> {
+        self.bounds
+    }
+
+    fn offset(&self, p: Pos<usize>) -> Option<usize> {
+        if p.in_bounds(&self.bounds) {
+            Some(p.y * self.bounds.x + p.x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, p: Pos<usize>) -> Option<&T> {
+        self.offset(p).map(|i| &self.tiles[i])
+    }
+
+    pub fn get_mut(&mut self, p: Pos<usize>) -> Option<&mut T> {
+        self.offset(p).map(|i| &mut self.tiles[i])
+    }
+
+    /// One step from `p`, or `None` off the edge of the grid
+    pub fn go(&self, p: Pos<usize>, dir: Dir) -> Option<Pos<usize>> {
+        p.go_bounded(dir, &self.bounds)
+    }
+
+    /// The in-bounds orthogonal neighbours of `p`, with the direction to each
+    pub fn neighbours(&self, p: Pos<usize>) -> impl Iterator<Item = (Dir, Pos<usize>)> + '_ {
+        enum_iterator::all::<Dir>().filter_map(move |dir| Some((dir, self.go(p, dir)?)))
+    }
+
+    /// One step from `p`, diagonals included
+    pub fn go8(&self, p: Pos<usize>, dir: Dir8) -> Option<Pos<usize>> {
+        p.go8_bounded(dir, &self.bounds)
+    }
+
+    /// The in-bounds king-move neighbours of `p`, clockwise from up
+    pub fn neighbours8(&self, p: Pos<usize>) -> impl Iterator<Item = (Dir8, Pos<usize>)> {
+        p.neighbours8(self.bounds)
+    }
+
+    /// All positions, row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos<usize>> {
+        self.bounds.generator()
+    }
+
+    /// All tiles with their positions, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Pos<usize>, &T)> {
+        self.positions().zip(self.tiles.iter())
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            bounds: self.bounds,
+            tiles: self.tiles.iter().map(f).collect(),
+        }
+    }
+
+    /// Draws the grid a row per line, with `f` choosing the char for each
+    /// tile, e.g. to overlay a path.
+    pub fn render_with(&self, mut f: impl FnMut(Pos<usize>, &T) -> char) -> String {
+        let mut out = String::with_capacity((self.bounds.x + 1) * self.bounds.y);
+        for (p, tile) in self.iter() {
+            out.push(f(p, tile));
+            if p.x == self.bounds.x - 1 {
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+impl<T: Render> Grid<T> {
+    /// Position of the first tile drawn as `c`, e.g. a start marker
+    pub fn find(&self, c: char) -> Option<Pos<usize>> {
+        self.iter().find(|(_, t)| t.render() == c).map(|(p, _)| p)
+    }
+}
+
+impl<T> Index<Pos<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Pos<usize>) -> &T {
+        match self.offset(p) {
+            Some(i) => &self.tiles[i],
+            None => panic!("{} out of bounds {}", p, self.bounds),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos<usize>> for Grid<T> {
+    fn index_mut(&mut self, p: Pos<usize>) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.tiles[i],
+            None => panic!("{} out of bounds {}", p, self.bounds),
+        }
+    }
+}
+
+impl<T: Render> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (p, tile) in self.iter() {
+            f.write_char(tile.render())?;
+            if p.x == self.bounds.x - 1 {
+                f.write_char('\n')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    fn grid() -> Result<Grid<char>> {
+        Grid::from_lines(["#S.", ".#E"], Ok)
+    }
+
+    #[test]
+    fn test_from_lines() -> Result<()> {
+        let g = grid()?;
+        assert_eq!(g.bounds(), Pos { x: 3, y: 2 });
+        assert_eq!(g[Pos { x: 2, y: 1 }], 'E');
+        assert_eq!(g.get(Pos { x: 3, y: 0 }), None);
+        assert_eq!(g.find('S'), Some(Pos { x: 1, y: 0 }));
+        assert_eq!(g.find('X'), None);
+        assert!(Grid::from_lines(["ab", "c"], Ok).is_err());
+        assert!(Grid::<char>::from_lines(Vec::<String>::new(), Ok).is_err());
+        assert!(Grid::from_lines(["ab"], |c| match c {
+            'a' => Ok(0),
+            _ => bail!("invalid tile {}", c),
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_neighbours() -> Result<()> {
+        let g = grid()?;
+        let corner: Vec<_> = g.neighbours(Pos { x: 0, y: 0 }).collect();
+        assert_eq!(
+            corner,
+            vec![
+                (Dir::Down, Pos { x: 0, y: 1 }),
+                (Dir::Right, Pos { x: 1, y: 0 })
+            ]
+        );
+        assert_eq!(g.neighbours(Pos { x: 1, y: 1 }).count(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render() -> Result<()> {
+        let mut g = grid()?;
+        assert_eq!(g.to_string(), "#S.\n.#E\n");
+        g[Pos { x: 2, y: 0 }] = 'O';
+        let marked = g.render_with(|p, c| if p.y == 1 { '~' } else { *c });
+        assert_eq!(marked, "#SO\n~~~\n");
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+
+use super::{Grid, ParseError, Pos};
+
+/// A day's puzzle input. Line endings are normalised to `\n` and trailing
+/// newlines dropped, so every day sees the same text however the file was
+/// saved or pasted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Input {
+    text: String,
+    /// Index of the first line within the whole input, so errors in a
+    /// section still point at the right line
+    first_line: usize,
+}
+
+/// One line of an input, remembering where it was for error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    /// 0-based within the whole input, even for a line of a section
+    pub index: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// The whole line is wrong
+    pub fn error(&self, message: impl Display) -> ParseError {
+        ParseError::line(self.index, self.text, message)
+    }
+
+    /// The char at `column` (0-based) is wrong
+    pub fn error_at(&self, column: usize, message: impl Display) -> ParseError {
+        ParseError::at(self.index, column, 1, self.text, message)
+    }
+
+    /// `part`, a slice of this line, is wrong
+    pub fn error_in(&self, part: &str, message: impl Display) -> ParseError {
+        ParseError::within(self.index, self.text, part, message)
+    }
+
+    /// Splits around the first `sep`. `expected` describes the line's
+    /// format for the error, e.g. "`wire: value`".
+    pub fn split_once(&self, sep: &str, expected: &str) -> Result<(&'a str, &'a str), ParseError> {
+        self.text
+            .split_once(sep)
+            .ok_or_else(|| self.error(format!("expected {}", expected)))
+    }
+
+    /// Parses `part`, a slice of this line, pointing at it on failure
+    pub fn parse<T>(&self, part: &str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        part.parse()
+            .map_err(|e| self.error_in(part, format!("invalid number {:?}: {}", part, e)))
+    }
+
+    /// Every integer in the line, as for `Input::numbers`
+    pub fn numbers<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut numbers = Vec::new();
+        let bytes = self.text.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if !bytes[i].is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+            let start = if i > 0 && bytes[i - 1] == b'-' {
+                i - 1
+            } else {
+                i
+            };
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            numbers.push(self.parse(&self.text[start..i])?);
+        }
+        Ok(numbers)
+    }
+}
+
+impl Input {
+    pub fn new(text: &str) -> Self {
+        let text = text.replace("\r\n", "\n");
+        Self {
+            text: text.trim_end_matches('\n').to_owned(),
+            first_line: 0,
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let text =
+            fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Ok(Self::new(&text))
+    }
+
+    /// The whole input, without its trailing newline
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+
+    /// Lines along with where they are, for parsers that report errors
+    pub fn numbered_lines(&self) -> impl Iterator<Item = Line<'_>> {
+        self.lines().enumerate().map(|(i, text)| Line {
+            index: self.first_line + i,
+            text,
+        })
+    }
+
+    /// The first line, for inputs that are a single line of data
+    pub fn line(&self) -> Result<Line<'_>, ParseError> {
+        self.numbered_lines()
+            .next()
+            .ok_or_else(|| self.error_at_end("empty input"))
+    }
+
+    /// An error about something missing after the last line
+    pub fn error_at_end(&self, message: impl Display) -> ParseError {
+        ParseError::line(self.first_line + self.lines().count(), "", message)
+    }
+
+    /// Blocks of lines separated by blank lines. Runs of blank lines count
+    /// as one separator, and leading ones are ignored.
+    pub fn sections(&self) -> Vec<Input> {
+        let mut sections = Vec::new();
+        let mut cur: Vec<Line> = Vec::new();
+        let mut flush = |cur: &mut Vec<Line>| {
+            if let Some(first) = cur.first() {
+                let text: Vec<&str> = cur.iter().map(|l| l.text).collect();
+                sections.push(Input {
+                    text: text.join("\n"),
+                    first_line: first.index,
+                });
+                cur.clear();
+            }
+        };
+        for line in self.numbered_lines() {
+            if line.text.trim().is_empty() {
+                flush(&mut cur);
+            } else {
+                cur.push(line);
+            }
+        }
+        flush(&mut cur);
+        sections
+    }
+
+    /// Exactly `N` sections, named for the error when there are more or
+    /// fewer, e.g. `["map", "moves"]`
+    pub fn expect_sections<const N: usize>(
+        &self,
+        names: [&str; N],
+    ) -> Result<[Input; N], ParseError> {
+        let mut sections = self.sections();
+        if sections.len() < N {
+            let missing = names[sections.len()];
+            return Err(match sections.last() {
+                Some(_) => self.error_at_end(format!("expected {} after a blank line", missing)),
+                None => self.error_at_end(format!("expected {}", missing)),
+            });
+        }
+        if let Some(extra) = sections.get(N) {
+            let line = extra.line()?;
+            return Err(line.error(format!("unexpected section after the {}", names[N - 1])));
+        }
+        sections.truncate(N);
+        Ok(sections.try_into().expect("exactly N sections"))
+    }
+
+    /// One row per line, each char converted to a tile
+    pub fn grid<T>(&self) -> Result<Grid<T>, ParseError>
+    where
+        T: TryFrom<char>,
+        T::Error: Display,
+    {
+        self.grid_with(|c| T::try_from(c).map_err(|e| format!("invalid tile {:?}: {}", c, e)))
+    }
+
+    /// One row per line, each char converted to a tile by `tile`, which
+    /// explains any char it rejects. All rows must be the same width.
+    pub fn grid_with<T, E, F>(&self, mut tile: F) -> Result<Grid<T>, ParseError>
+    where
+        E: Display,
+        F: FnMut(char) -> Result<T, E>,
+    {
+        let mut tiles = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for line in self.numbered_lines() {
+            let before = tiles.len();
+            for (x, c) in line.text.chars().enumerate() {
+                tiles.push(tile(c).map_err(|e| line.error_at(x, e))?);
+            }
+            let w = tiles.len() - before;
+            match width {
+                None => width = Some(w),
+                Some(width) if width != w => {
+                    return Err(ParseError::at(
+                        line.index,
+                        w.min(width),
+                        w.abs_diff(width),
+                        line.text,
+                        format!("row is {} wide, expected {}", w, width),
+                    ));
+                }
+                _ => {}
+            }
+            height += 1;
+        }
+        match width {
+            Some(width) if width > 0 => Ok(Grid::from_tiles(
+                Pos {
+                    x: width,
+                    y: height,
+                },
+                tiles,
+            )),
+            _ => Err(self.error_at_end("empty grid")),
+        }
+    }
+
+    /// Every integer in the text, in order. A `-` directly before the digits
+    /// makes it negative.
+    pub fn numbers<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let mut numbers = Vec::new();
+        for line in self.numbered_lines() {
+            numbers.extend(line.numbers()?);
+        }
+        Ok(numbers)
+    }
+}
+
+impl From<&str> for Input {
+    fn from(text: &str) -> Self {
+        Self::new(text)
+    }
+}
+
+impl From<String> for Input {
+    fn from(text: String) -> Self {
+        Self::new(&text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn test_line_endings() {
+        let unix = Input::new("ab\ncd\n\nef\n\n");
+        let dos = Input::new("ab\r\ncd\r\n\r\nef\r\n");
+        assert_eq!(unix, dos);
+        assert_eq!(unix.text(), "ab\ncd\n\nef");
+        assert_eq!(dos.lines().collect::<Vec<_>>(), vec!["ab", "cd", "", "ef"]);
+        assert_eq!(Input::new("").lines().count(), 0);
+        assert!(Input::new("\n").line().is_err());
+    }
+
+    #[test]
+    fn test_sections() -> Result<()> {
+        let input = Input::new("\na\nb\n\n\nc\n  \nd\n");
+        let sections: Vec<_> = input.sections();
+        let texts: Vec<_> = sections.iter().map(|s| s.text()).collect();
+        assert_eq!(texts, vec!["a\nb", "c", "d"]);
+        assert!(Input::new("").sections().is_empty());
+
+        // Lines are still numbered from the top of the whole input
+        let line = sections[1].line()?;
+        assert_eq!((line.index, line.text), (5, "c"));
+        assert_eq!(sections[2].error_at_end("more").line, 9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_expect_sections() -> Result<()> {
+        let input = Input::new("a\n\nb\nc\n\nd");
+        let e = input.expect_sections(["x", "y"]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 6: unexpected section after the y\n   6 | d\n     | ^"
+        );
+        let [x, y, z] = input.expect_sections(["x", "y", "z"])?;
+        assert_eq!((x.text(), y.text(), z.text()), ("a", "b\nc", "d"));
+        let e = input.expect_sections(["x", "y", "z", "w"]).unwrap_err();
+        assert_eq!(e.to_string(), "line 7: expected w after a blank line");
+        let e = Input::new("").expect_sections(["rules"]).unwrap_err();
+        assert_eq!(e.to_string(), "line 1: expected rules");
+        Ok(())
+    }
+
+    #[test]
+    fn test_grid() -> Result<()> {
+        let grid = Input::new("ab\r\ncd\r\n").grid::<char>()?;
+        assert_eq!(grid.bounds(), Pos { x: 2, y: 2 });
+        assert_eq!(grid[Pos { x: 0, y: 1 }], 'c');
+
+        let e = Input::new("ab\nc").grid::<char>().unwrap_err();
+        assert_eq!((e.line, e.column), (2, Some(2)));
+        let e = Input::new("\n\n").grid::<char>().unwrap_err();
+        assert_eq!(e.message, "empty grid");
+
+        let sections = Input::new("..\n\n..\n.x").sections();
+        let e = sections[1]
+            .grid_with(|c| if c == '.' { Ok(()) } else { Err("bad") })
+            .unwrap_err();
+        assert_eq!(e.to_string(), "line 4, column 2: bad\n   4 | .x\n     |  ^");
+        Ok(())
+    }
+
+    #[test]
+    fn test_numbers() -> Result<()> {
+        let input = Input::new("p=0,4 v=3,-3\nButton A: X+94, Y-34\n1-2");
+        assert_eq!(input.numbers::<i64>()?, vec![0, 4, 3, -3, 94, -34, 1, -2]);
+        let e = input.numbers::<u8>().unwrap_err();
+        assert_eq!((e.line, e.column, e.width), (1, Some(11), 2));
+        assert!(Input::new("300").numbers::<u8>().is_err());
+        assert!(Input::new("no digits").numbers::<u8>()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_line_helpers() {
+        let input = Input::new("x00: 1\ny01 2");
+        let lines: Vec<_> = input.numbered_lines().collect();
+        assert_eq!(lines[0].split_once(": ", "`wire: value`"), Ok(("x00", "1")));
+        let e = lines[1].split_once(": ", "`wire: value`").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2: expected `wire: value`\n   2 | y01 2\n     | ^^^^^"
+        );
+        let e = lines[1].parse::<u8>(&lines[1].text[..3]).unwrap_err();
+        assert_eq!((e.column, e.width), (Some(1), 3));
+        assert_eq!(lines[0].parse::<u8>(&lines[0].text[5..]), Ok(1));
+    }
+}
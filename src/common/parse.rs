@@ -0,0 +1,128 @@
+use std::fmt::{self, Display};
+
+/// A problem with the puzzle input at a known place. Displays as a summary
+/// line followed by the offending line with a caret under the bad part:
+///
+/// ```text
+/// line 3, column 4: invalid tile 'x'
+///    3 | ..#x.
+///      |    ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based, as editors count
+    pub line: usize,
+    /// 1-based char column, or `None` when the whole line is at fault
+    pub column: Option<usize>,
+    /// How many chars the caret spans
+    pub width: usize,
+    /// The whole offending line
+    pub text: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// About the whole of line `index` (0-based, as from `enumerate`)
+    pub fn line(index: usize, text: &str, message: impl Display) -> Self {
+        Self {
+            line: index + 1,
+            column: None,
+            width: text.chars().count(),
+            text: text.to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// About `width` chars from char `column` of line `index`, both 0-based
+    pub fn at(
+        index: usize,
+        column: usize,
+        width: usize,
+        text: &str,
+        message: impl Display,
+    ) -> Self {
+        Self {
+            line: index + 1,
+            column: Some(column + 1),
+            width: width.max(1),
+            text: text.to_owned(),
+            message: message.to_string(),
+        }
+    }
+
+    /// About `part` of line `index`. `part` should be a slice of `text`;
+    /// failing that the first occurrence is used, then the whole line.
+    pub fn within(index: usize, text: &str, part: &str, message: impl Display) -> Self {
+        let start = (part.as_ptr() as usize)
+            .checked_sub(text.as_ptr() as usize)
+            .filter(|&offset| offset + part.len() <= text.len())
+            .or_else(|| text.find(part));
+        match start {
+            Some(start) if text.is_char_boundary(start) => Self::at(
+                index,
+                text[..start].chars().count(),
+                part.chars().count(),
+                text,
+                message,
+            ),
+            _ => Self::line(index, text, message),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "line {}, column {}: {}", self.line, column, self.message)?,
+            None => write!(f, "line {}: {}", self.line, self.message)?,
+        }
+        if self.text.is_empty() {
+            return Ok(());
+        }
+        let gutter = self.line.to_string().len().max(4);
+        let indent = self.column.map_or(0, |c| c - 1);
+        write!(
+            f,
+            "\n{:>gutter$} | {}\n{:>gutter$} | {}{}",
+            self.line,
+            self.text,
+            "",
+            " ".repeat(indent),
+            "^".repeat(self.width.max(1)),
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let e = ParseError::at(2, 3, 1, "..#x.", "invalid tile 'x'");
+        assert_eq!(
+            e.to_string(),
+            "line 3, column 4: invalid tile 'x'\n   3 | ..#x.\n     |    ^"
+        );
+        let e = ParseError::line(11, "abc", "expected a number");
+        assert_eq!(
+            e.to_string(),
+            "line 12: expected a number\n  12 | abc\n     | ^^^"
+        );
+        let e = ParseError::line(0, "", "empty input");
+        assert_eq!(e.to_string(), "line 1: empty input");
+    }
+
+    #[test]
+    fn test_within() {
+        let text = "p=1,2 v=é,4";
+        let e = ParseError::within(0, text, &text[8..10], "bad");
+        assert_eq!((e.column, e.width), (Some(9), 1));
+        let e = ParseError::within(0, text, "v=", "bad");
+        assert_eq!((e.column, e.width), (Some(7), 2));
+        let e = ParseError::within(0, text, "nope", "bad");
+        assert_eq!((e.column, e.width), (None, 11));
+    }
+}
@@ -0,0 +1,307 @@
+//! Shortest paths over implicit graphs. Nodes are anything hashable, and
+//! edges come from a neighbour closure, so nothing has to be built up front.
+
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+use super::Pos;
+
+/// Costs from a start node, with every predecessor that lies on a shortest
+/// path to each node.
+#[derive(Debug, Clone)]
+pub struct Paths<N> {
+    start: N,
+    costs: HashMap<N, usize>,
+    preds: HashMap<N, Vec<N>>,
+}
+
+impl<N: Copy + Eq + Hash> Paths<N> {
+    fn new(start: N) -> Self {
+        Self {
+            start,
+            costs: HashMap::from([(start, 0)]),
+            preds: HashMap::new(),
+        }
+    }
+
+    /// Records reaching `to` via `from` at `cost`. Returns whether that is a
+    /// strict improvement, i.e. `to` needs (re)visiting.
+    fn relax(&mut self, from: N, to: N, cost: usize) -> bool {
+        match self.costs.entry(to) {
+            Entry::Vacant(e) => {
+                e.insert(cost);
+                self.preds.insert(to, vec![from]);
+                true
+            }
+            Entry::Occupied(mut e) => match cost.cmp(e.get()) {
+                Ordering::Less => {
+                    e.insert(cost);
+                    self.preds.insert(to, vec![from]);
+                    true
+                }
+                Ordering::Equal => {
+                    self.preds.entry(to).or_default().push(from);
+                    false
+                }
+                Ordering::Greater => false,
+            },
+        }
+    }
+
+    /// Lowest cost to reach `node`, if it was reached
+    pub fn cost(&self, node: &N) -> Option<usize> {
+        self.costs.get(node).copied()
+    }
+
+    pub fn costs(&self) -> &HashMap<N, usize> {
+        &self.costs
+    }
+
+    /// Nodes one step before `node` on its shortest paths
+    pub fn preds(&self, node: &N) -> &[N] {
+        self.preds.get(node).map_or(&[], |p| p.as_slice())
+    }
+
+    /// One shortest path to `end`, start and end included
+    pub fn path_to(&self, end: N) -> Option<Vec<N>> {
+        self.costs.get(&end)?;
+        let mut path = vec![end];
+        let mut node = end;
+        // Zero-cost edges can give `start` predecessors of its own, which
+        // would lead round in a cycle
+        while node != self.start {
+            let prev = self.preds(&node).first()?;
+            path.push(*prev);
+            node = *prev;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every node on any shortest path to any of `ends`. Pass all the ends
+    /// that share the lowest cost, e.g. one goal reached in several states.
+    pub fn on_paths_to(&self, ends: impl IntoIterator<Item = N>) -> HashSet<N> {
+        let mut seen = HashSet::new();
+        let mut todo: Vec<N> = ends
+            .into_iter()
+            .filter(|n| self.costs.contains_key(n))
+            .collect();
+        while let Some(node) = todo.pop() {
+            if seen.insert(node) {
+                todo.extend(self.preds(&node));
+            }
+        }
+        seen
+    }
+}
+
+/// Breadth-first search where every step costs 1
+pub fn bfs<N, F, I>(start: N, mut neighbours: F) -> Paths<N>
+where
+    N: Copy + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = N>,
+{
+    let mut paths = Paths::new(start);
+    let mut queue = VecDeque::from([start]);
+    while let Some(u) = queue.pop_front() {
+        let cost = paths.costs[&u] + 1;
+        for v in neighbours(&u) {
+            if paths.relax(u, v, cost) {
+                queue.push_back(v);
+            }
+        }
+    }
+    paths
+}
+
+/// Heap entry that pops lowest estimate first, so nodes don't need to be `Ord`
+struct Queued<N> {
+    estimate: usize,
+    cost: usize,
+    node: N,
+}
+
+impl<N> PartialEq for Queued<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+impl<N> Eq for Queued<N> {}
+impl<N> PartialOrd for Queued<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<N> Ord for Queued<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.cmp(&self.estimate)
+    }
+}
+
+/// Dijkstra's algorithm over `(neighbour, step cost)` pairs, visiting every
+/// node reachable from `start`
+pub fn dijkstra<N, F, I>(start: N, neighbours: F) -> Paths<N>
+where
+    N: Copy + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    search(start, neighbours, |_| 0, |_| false)
+}
+
+/// A* from `start` until `is_goal`, guided by `heuristic`, which must never
+/// overestimate the remaining cost. Returns the goal reached, its cost and a
+/// path to it.
+pub fn astar<N, F, I, H, G>(
+    start: N,
+    neighbours: F,
+    heuristic: H,
+    mut is_goal: G,
+) -> Option<(usize, Vec<N>)>
+where
+    N: Copy + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    H: FnMut(&N) -> usize,
+    G: FnMut(&N) -> bool,
+{
+    let mut reached = None;
+    let paths = search(start, neighbours, heuristic, |n| {
+        if is_goal(n) {
+            reached = Some(*n);
+            true
+        } else {
+            false
+        }
+    });
+    let goal = reached?;
+    Some((paths.cost(&goal)?, paths.path_to(goal)?))
+}
+
+/// A* between two grid positions, using the Manhattan distance as heuristic
+pub fn astar_manhattan<F, I>(
+    start: Pos<usize>,
+    goal: Pos<usize>,
+    neighbours: F,
+) -> Option<(usize, Vec<Pos<usize>>)>
+where
+    F: FnMut(&Pos<usize>) -> I,
+    I: IntoIterator<Item = (Pos<usize>, usize)>,
+{
+    astar(start, neighbours, |p| p.manhattan(&goal), |p| *p == goal)
+}
+
+fn search<N, F, I, H, G>(start: N, mut neighbours: F, mut heuristic: H, mut stop: G) -> Paths<N>
+where
+    N: Copy + Eq + Hash,
+    F: FnMut(&N) -> I,
+    I: IntoIterator<Item = (N, usize)>,
+    H: FnMut(&N) -> usize,
+    G: FnMut(&N) -> bool,
+{
+    let mut paths = Paths::new(start);
+    let mut heap = BinaryHeap::from([Queued {
+        estimate: heuristic(&start),
+        cost: 0,
+        node: start,
+    }]);
+    while let Some(Queued { cost, node: u, .. }) = heap.pop() {
+        if cost > paths.costs[&u] {
+            continue; // stale entry
+        }
+        if stop(&u) {
+            break;
+        }
+        for (v, step) in neighbours(&u) {
+            let cost = cost + step;
+            if paths.relax(u, v, cost) {
+                heap.push(Queued {
+                    estimate: cost + heuristic(&v),
+                    cost,
+                    node: v,
+                });
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::common::Grid;
+
+    fn maze() -> Grid<char> {
+        Grid::from_lines(["S..#", ".#..", "...E"], Ok).unwrap()
+    }
+
+    fn open(g: &Grid<char>, p: &Pos<usize>) -> Vec<Pos<usize>> {
+        g.neighbours(*p)
+            .filter(|(_, q)| g[*q] != '#')
+            .map(|(_, q)| q)
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let g = maze();
+        let start = g.find('S').unwrap();
+        let end = g.find('E').unwrap();
+        let paths = bfs(start, |p| open(&g, p));
+        assert_eq!(paths.cost(&end), Some(5));
+        assert_eq!(paths.cost(&Pos { x: 3, y: 0 }), None);
+        let path = paths.path_to(end).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path[0], start);
+        // Every open tile is on one of the shortest paths
+        assert_eq!(paths.on_paths_to([end]).len(), 10);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Stepping into the bottom row costs 10, so go along the top
+        let g = maze();
+        let start = g.find('S').unwrap();
+        let end = g.find('E').unwrap();
+        let paths = dijkstra(start, |p| {
+            open(&g, p)
+                .into_iter()
+                .map(|q| (q, if q.y == 2 { 10 } else { 1 }))
+        });
+        assert_eq!(paths.cost(&end), Some(14));
+        assert_eq!(paths.preds(&end), &[Pos { x: 3, y: 1 }]);
+        assert_eq!(paths.on_paths_to([end]).len(), 6);
+    }
+
+    #[test]
+    fn test_zero_cost_cycle() {
+        // 0 <-> 1 for free, then 1 -> 2 for 1
+        let paths = dijkstra(0, |&n: &u8| match n {
+            0 => vec![(1, 0)],
+            1 => vec![(0, 0), (2, 1)],
+            _ => vec![],
+        });
+        assert_eq!(paths.preds(&0), &[1]);
+        assert_eq!(paths.path_to(2), Some(vec![0, 1, 2]));
+        assert_eq!(paths.path_to(0), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_astar() {
+        let g = maze();
+        let start = g.find('S').unwrap();
+        let end = g.find('E').unwrap();
+        let (cost, path) =
+            astar_manhattan(start, end, |p| open(&g, p).into_iter().map(|q| (q, 1))).unwrap();
+        assert_eq!(cost, 5);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&end));
+        let unreachable = astar_manhattan(start, Pos { x: 3, y: 0 }, |p| {
+            open(&g, p).into_iter().map(|q| (q, 1))
+        });
+        assert_eq!(unreachable, None);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use toml::{Table, Value};
+
+/// The year every day belongs to, unless configured otherwise
+pub const DEFAULT_YEAR: u16 = 2024;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+/// Settings for talking to the puzzle site, from `aoc.toml`:
+///
+/// ```toml
+/// year = 2024
+/// session = "53616c7465645f5f..."
+/// base_url = "https://adventofcode.com"
+/// ```
+///
+/// `AOC_SESSION` and `AOC_BASE_URL` in the environment take precedence, so
+/// the token can stay out of files entirely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub year: u16,
+    /// The site's `session` cookie, needed for anything per-user
+    pub session: Option<String>,
+    pub base_url: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            year: DEFAULT_YEAR,
+            session: None,
+            base_url: DEFAULT_BASE_URL.to_owned(),
+        }
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let mut config = Self::default();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("year", Value::Integer(year)) => {
+                    config.year = year
+                        .try_into()
+                        .map_err(|_| anyhow!("invalid year {}", year))?;
+                }
+                ("session", Value::String(s)) => config.session = Some(s),
+                ("base_url", Value::String(s)) => config.base_url = s,
+                ("year" | "session" | "base_url", v) => bail!("invalid value {} for {}", v, key),
+                _ => bail!("unknown key {}", key),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Loads the config from `path`, then applies the environment. A missing
+    /// file just means the defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config = if path.exists() {
+            let text =
+                fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+            Self::parse(&text).with_context(|| format!("in {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        config.apply_env(|key| std::env::var(key).ok());
+        Ok(config)
+    }
+
+    /// Overrides settings from environment variables, looked up by `var`
+    pub fn apply_env(&mut self, var: impl Fn(&str) -> Option<String>) {
+        if let Some(session) = var("AOC_SESSION").filter(|s| !s.trim().is_empty()) {
+            self.session = Some(session.trim().to_owned());
+        }
+        if let Some(base_url) = var("AOC_BASE_URL").filter(|s| !s.trim().is_empty()) {
+            self.base_url = base_url.trim().to_owned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<()> {
+        let config = Config::parse(indoc! {r#"
+            year = 2023
+            session = "abc123"
+        "#})?;
+        assert_eq!(config.year, 2023);
+        assert_eq!(config.session.as_deref(), Some("abc123"));
+        assert_eq!(config.base_url, DEFAULT_BASE_URL);
+        assert_eq!(Config::parse("")?, Config::default());
+
+        assert!(Config::parse("year = \"2024\"").is_err());
+        assert!(Config::parse("year = 99999").is_err());
+        assert!(Config::parse("token = \"abc\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_env() -> Result<()> {
+        let mut config = Config::parse("session = \"from-file\"")?;
+        config.apply_env(|key| match key {
+            "AOC_SESSION" => Some("from-env\n".to_owned()),
+            "AOC_BASE_URL" => Some("http://127.0.0.1:8080".to_owned()),
+            _ => None,
+        });
+        assert_eq!(config.session.as_deref(), Some("from-env"));
+        assert_eq!(config.base_url, "http://127.0.0.1:8080");
+
+        // Empty variables don't wipe out the file's settings
+        let mut config = Config::parse("session = \"from-file\"")?;
+        config.apply_env(|_| Some(String::new()));
+        assert_eq!(config.session.as_deref(), Some("from-file"));
+        Ok(())
+    }
+}
@@ -1,19 +1,34 @@
-use anyhow::{bail, Result};
+use anyhow::Result;
+use log::trace;
+use std::fmt::Display;
 use std::iter::zip;
+use std::str::FromStr;
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut a: Vec<u32> = Vec::new();
-    let mut b: Vec<u32> = Vec::new();
+use crate::common::Input;
+use crate::solver::{parsing, Params, Solver};
 
-    for line in lines {
-        let parts: Vec<&str> = line.split_ascii_whitespace().collect();
-        if parts.len() > 2 {
-            bail!("line too long")
-        }
-        a.push(parts[0].parse()?);
-        b.push(parts[1].parse()?);
-        //println!("{}", parts.join(","))
+/// The two lists, side by side with one ID from each per line
+fn parse<T>(input: &Input) -> Result<(Vec<T>, Vec<T>)>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    for line in input.numbered_lines() {
+        let parts: Vec<&str> = line.text.split_ascii_whitespace().collect();
+        let [left, right] = parts[..] else {
+            return Err(line.error("expected two location IDs").into());
+        };
+        a.push(line.parse(left)?);
+        b.push(line.parse(right)?);
+        trace!("{}", parts.join(","));
     }
+    Ok((a, b))
+}
+
+pub fn part1(input: &Input) -> Result<String> {
+    let (mut a, mut b): (Vec<u32>, Vec<u32>) = parsing(|| parse(input))?;
     a.sort();
     b.sort();
     let c = zip(a, b);
@@ -21,19 +36,8 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
     Ok(sum_diff.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut a: Vec<usize> = Vec::new();
-    let mut b: Vec<usize> = Vec::new();
-
-    for line in lines {
-        let parts: Vec<&str> = line.split_ascii_whitespace().collect();
-        if parts.len() > 2 {
-            bail!("line too long")
-        }
-        a.push(parts[0].parse()?);
-        b.push(parts[1].parse()?);
-        //println!("{}", parts.join(","))
-    }
+pub fn part2(input: &Input) -> Result<String> {
+    let (a, b): (Vec<usize>, Vec<usize>) = parsing(|| parse(input))?;
     let mut total: usize = 0;
     for x in &a {
         let mut count: usize = 0;
@@ -47,6 +51,21 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
+pub struct Day01;
+impl Solver for Day01 {
+    fn day(&self) -> u8 {
+        1
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -56,36 +75,30 @@ mod test {
 
     #[test]
     pub fn test_part1() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             3   4
             4   3
             2   5
             1   3
             3   9
             3   3
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
+        "});
 
-        assert_eq!(part1(input)?, "11");
+        assert_eq!(part1(&input)?, "11");
         Ok(())
     }
 
     #[test]
     pub fn test_part2() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             3   4
             4   3
             2   5
             1   3
             3   9
             3   3
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
-        assert_eq!(part2(input)?, "31");
+        "});
+        assert_eq!(part2(&input)?, "31");
         Ok(())
     }
 }
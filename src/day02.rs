@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use crate::common::{Input, Line};
+use crate::solver::{parsing, Params, Solver};
+
+fn parse(line: Line) -> Result<Vec<isize>> {
+    let mut levels = Vec::new();
+    for x in line.text.split_whitespace() {
+        levels.push(line.parse(x)?);
+    }
+    if levels.is_empty() {
+        return Err(line.error("expected a report of levels").into());
+    }
+    Ok(levels)
+}
+
+fn is_safe(levels: &[isize]) -> bool {
+    let increasing = levels.windows(2).all(|w| (1..=3).contains(&(w[1] - w[0])));
+    let decreasing = levels.windows(2).all(|w| (1..=3).contains(&(w[0] - w[1])));
+    increasing || decreasing
+}
+
+/// Safe as-is, or safe after the Problem Dampener removes any one level
+fn is_dampened_safe(levels: &[isize]) -> bool {
+    if is_safe(levels) {
+        return true;
+    }
+    (0..levels.len()).any(|i| {
+        let mut fewer = levels.to_vec();
+        fewer.remove(i);
+        is_safe(&fewer)
+    })
+}
+
+pub fn part1(input: &Input) -> Result<String> {
+    let mut total: usize = 0;
+    for line in input.numbered_lines() {
+        if is_safe(&parsing(|| parse(line))?) {
+            total += 1;
+        }
+    }
+    Ok(total.to_string())
+}
+
+pub fn part2(input: &Input) -> Result<String> {
+    let mut total: usize = 0;
+    for line in input.numbered_lines() {
+        if is_dampened_safe(&parsing(|| parse(line))?) {
+            total += 1;
+        }
+    }
+    Ok(total.to_string())
+}
+
+pub struct Day02;
+impl Solver for Day02 {
+    fn day(&self) -> u8 {
+        2
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use indoc::indoc;
+
+    use super::*;
+
+    fn input() -> Input {
+        Input::new(indoc! {"
+            7 6 4 2 1
+            1 2 7 8 9
+            9 7 6 2 1
+            1 3 2 4 5
+            8 6 4 4 1
+            1 3 6 7 9
+        "})
+    }
+
+    #[test]
+    fn test_part1() -> Result<()> {
+        assert_eq!(part1(&input())?, "2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_dampened() -> Result<()> {
+        let expect = [true, false, false, true, true, true];
+        for (line, safe) in input().numbered_lines().zip(expect) {
+            assert_eq!(is_dampened_safe(&parse(line)?), safe, "{}", line.text);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        assert_eq!(part2(&input())?, "4");
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::Result;
+use regex::Regex;
+
+use crate::common::Input;
+use crate::solver::{Params, Solver};
+
+/// Sums the `mul(X,Y)` instructions, honouring `do()`/`don't()` when
+/// `conditional` is set. The enabled state carries over between lines.
+fn sum_muls(input: &Input, conditional: bool) -> Result<String> {
+    let re = Regex::new(r"mul\(([0-9]{1,3}),([0-9]{1,3})\)|do\(\)|don't\(\)").unwrap();
+    let mut enabled = true;
+    let mut total: usize = 0;
+    for line in input.lines() {
+        for cap in re.captures_iter(line) {
+            match &cap[0] {
+                "do()" => enabled = true,
+                "don't()" => enabled = !conditional,
+                _ => {
+                    if enabled {
+                        total += cap[1].parse::<usize>()? * cap[2].parse::<usize>()?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(total.to_string())
+}
+
+pub fn part1(input: &Input) -> Result<String> {
+    sum_muls(input, false)
+}
+
+pub fn part2(input: &Input) -> Result<String> {
+    sum_muls(input, true)
+}
+
+pub struct Day03;
+impl Solver for Day03 {
+    fn day(&self) -> u8 {
+        3
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    fn input() -> Input {
+        Input::new("xmul(2,4)&mul[3,7]!^don't()\n_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))")
+    }
+
+    #[test]
+    fn test_part1() -> Result<()> {
+        assert_eq!(part1(&input())?, "161");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        assert_eq!(part2(&input())?, "48");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part1_too_many_digits() -> Result<()> {
+        let input = Input::new("mul(1234,5)mul(123,4)mul( 1,2)");
+        assert_eq!(part1(&input)?, "492");
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+
+use crate::common::{Dir8, Grid, Input, Pos};
+use crate::solver::{parsing, Params, Solver};
+
+struct Puzzle {
+    grid: Grid<char>,
+}
+
+impl Puzzle {
+    fn parse(input: &Input) -> Result<Self> {
+        let grid = input.grid()?;
+        Ok(Self { grid })
+    }
+
+    fn spells(&self, mut p: Pos<usize>, dir: Dir8, word: &str) -> bool {
+        for (i, c) in word.chars().enumerate() {
+            if i > 0 {
+                match self.grid.go8(p, dir) {
+                    Some(next) => p = next,
+                    None => return false,
+                }
+            }
+            if self.grid[p] != c {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// An `A` with two `MAS` crossing diagonally through it
+    fn is_x_mas(&self, p: Pos<usize>) -> bool {
+        if self.grid[p] != 'A' {
+            return false;
+        }
+        // clockwise from up left
+        let mut corners = String::with_capacity(4);
+        let mut dir = Dir8::UpLeft;
+        for _ in 0..4 {
+            match self.grid.go8(p, dir) {
+                Some(c) => corners.push(self.grid[c]),
+                None => return false,
+            }
+            dir = dir.cw90();
+        }
+        matches!(corners.as_str(), "MMSS" | "SMMS" | "SSMM" | "MSSM")
+    }
+}
+
+pub fn part1(input: &Input) -> Result<String> {
+    let puzzle = parsing(|| Puzzle::parse(input))?;
+    let total = puzzle
+        .grid
+        .positions()
+        .map(|p| {
+            enum_iterator::all::<Dir8>()
+                .filter(|dir| puzzle.spells(p, *dir, "XMAS"))
+                .count()
+        })
+        .sum::<usize>();
+    Ok(total.to_string())
+}
+
+pub fn part2(input: &Input) -> Result<String> {
+    let puzzle = parsing(|| Puzzle::parse(input))?;
+    let total = puzzle
+        .grid
+        .positions()
+        .filter(|p| puzzle.is_x_mas(*p))
+        .count();
+    Ok(total.to_string())
+}
+
+pub struct Day04;
+impl Solver for Day04 {
+    fn day(&self) -> u8 {
+        4
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use indoc::indoc;
+
+    use super::*;
+
+    fn input() -> Input {
+        Input::new(indoc! {"
+            MMMSXXMASM
+            MSAMXMSMSA
+            AMXSXMAAMM
+            MSAMASMSMX
+            XMASAMXAMM
+            XXAMMXXAMA
+            SMSMSASXSS
+            SAXAMASAAA
+            MAMMMXMMMM
+            MXMXAXMASX
+        "})
+    }
+
+    #[test]
+    fn test_part1() -> Result<()> {
+        assert_eq!(part1(&input())?, "18");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        assert_eq!(part2(&input())?, "9");
+        Ok(())
+    }
+
+    #[test]
+    fn test_edges() -> Result<()> {
+        // Words running off the grid don't wrap around
+        let input = Input::new("MASX\nXSAM");
+        assert_eq!(part1(&input)?, "0");
+        assert_eq!(part2(&input)?, "0");
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
 use anyhow::Result;
+use log::debug;
 use std::cmp::Ordering;
 
+use crate::common::Input;
+use crate::solver::{parsing, Params, Solver};
+
 struct RuleIndex {
     rules: Vec<String>,
 }
@@ -16,21 +20,29 @@ impl RuleIndex {
     }
 }
 
-fn prep(lines: Vec<String>) -> (Vec<String>, RuleIndex) {
-    let (to_produce, mut rules): (Vec<_>, Vec<_>) =
-        lines.into_iter().partition(|x| x.contains(','));
-    _ = rules.pop();
-    let index = RuleIndex::new(rules);
-    (to_produce, index)
+fn prep(input: &Input) -> Result<(Vec<String>, RuleIndex)> {
+    let [rules, updates] = input.expect_sections(["rules", "updates"])?;
+    for line in rules.numbered_lines() {
+        let (before, after) = line.split_once("|", "a rule `X|Y`")?;
+        line.parse::<usize>(before)?;
+        line.parse::<usize>(after)?;
+    }
+    for line in updates.numbered_lines() {
+        for page in line.text.split(',') {
+            line.parse::<usize>(page)?;
+        }
+    }
+    let index = RuleIndex::new(rules.lines().map(String::from).collect());
+    Ok((updates.lines().map(String::from).collect(), index))
 }
 
-fn mid_num(pages: &Vec<&str>) -> Result<usize> {
+fn mid_num(pages: &[&str]) -> Result<usize> {
     let mid = pages.len() / 2;
     Ok(pages[mid].parse::<usize>()?)
 }
 
-fn rule_sort<'a>(pages: &Vec<&'a str>, index: &RuleIndex) -> Vec<&'a str> {
-    let mut sorted: Vec<&str> = pages.clone();
+fn rule_sort<'a>(pages: &[&'a str], index: &RuleIndex) -> Vec<&'a str> {
+    let mut sorted: Vec<&str> = pages.to_vec();
     sorted.sort_by(|a, b| {
         let c = format!("{}|{}", a, b);
         if index.contains(&c) {
@@ -42,11 +54,11 @@ fn rule_sort<'a>(pages: &Vec<&'a str>, index: &RuleIndex) -> Vec<&'a str> {
     sorted
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let (to_produce, index) = prep(lines);
+pub fn part1(input: &Input) -> Result<String> {
+    let (to_produce, index) = parsing(|| prep(input))?;
     let mut total: usize = 0;
     for p in to_produce {
-        println!("Checking: {}", p);
+        debug!("checking {}", p);
         let pages: Vec<&str> = p.split(',').collect();
         let sorted = rule_sort(&pages, &index);
         if pages == sorted {
@@ -57,11 +69,11 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let (to_produce, index) = prep(lines);
+pub fn part2(input: &Input) -> Result<String> {
+    let (to_produce, index) = parsing(|| prep(input))?;
     let mut total: usize = 0;
     for p in to_produce {
-        println!("Checking: {}", p);
+        debug!("checking {}", p);
         let pages: Vec<&str> = p.split(',').collect();
         let sorted = rule_sort(&pages, &index);
         if pages != sorted {
@@ -72,6 +84,21 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
+pub struct Day05;
+impl Solver for Day05 {
+    fn day(&self) -> u8 {
+        5
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -81,7 +108,7 @@ mod test {
 
     #[test]
     fn test_part1() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             47|53
             97|13
             97|61
@@ -110,18 +137,15 @@ mod test {
             75,97,47,61,53
             61,13,29
             97,13,75,29,47
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
+        "});
 
-        assert_eq!(part1(input)?, "143");
+        assert_eq!(part1(&input)?, "143");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             47|53
             97|13
             97|61
@@ -150,12 +174,9 @@ mod test {
             75,97,47,61,53
             61,13,29
             97,13,75,29,47
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
+        "});
 
-        assert_eq!(part2(input)?, "123");
+        assert_eq!(part2(&input)?, "123");
         Ok(())
     }
 }
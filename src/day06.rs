@@ -1,143 +1,56 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use log::trace;
 use std::collections::HashSet;
 
+use crate::common::{Dir, Grid, Input, Pos, Render};
+use crate::solver::{parsing, Params, Solver};
+type Point = Pos<usize>;
+
 #[derive(Debug, Clone, Copy)]
 enum Tile {
     Empty,
     Obst,
     Visited,
 }
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Pos {
-    x: usize,
-    y: usize,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
-    Up,
-    Left,
-    Down,
-    Right,
-}
-impl Dir {
-    pub fn cw(&self) -> Self {
+impl Render for Tile {
+    fn render(&self) -> char {
         match self {
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
+            Tile::Empty => '.',
+            Tile::Obst => '#',
+            Tile::Visited => 'X',
         }
     }
 }
 
 #[derive(Clone)] // Allow reality-forking
 struct Map {
-    bound: Pos,
     dir: Dir,
-    guard: Pos,
-    grid: Vec<Vec<Tile>>,
+    guard: Point,
+    grid: Grid<Tile>,
 }
 
 impl Map {
-    fn new(lines: Vec<String>) -> Self {
-        let h = lines.len();
-        let w = lines.first().expect("non-zero width").len();
-        let mut guard = Pos { x: 0, y: 0 };
-        let mut dir = Dir::Up;
-        let mut grid: Vec<Vec<Tile>> = Vec::new();
-        for y in 0..h {
-            let line = &lines[y];
-            let mut row: Vec<Tile> = Vec::new();
-            for (x, c) in line.chars().enumerate() {
-                let tile = match c {
-                    '.' => Tile::Empty,
-                    '#' => Tile::Obst,
-                    '^' => {
-                        guard = Pos { x, y };
-                        dir = Dir::Up;
-                        Tile::Visited
-                    }
-                    // unsure if guard can be any of these initially, my challenge has it facing up:
-                    '<' => {
-                        guard = Pos { x, y };
-                        dir = Dir::Left;
-                        Tile::Visited
-                    }
-                    '>' => {
-                        guard = Pos { x, y };
-                        dir = Dir::Right;
-                        Tile::Visited
-                    }
-                    'v' => {
-                        guard = Pos { x, y };
-                        dir = Dir::Down;
-                        Tile::Visited
-                    }
-                    _ => {
-                        panic!("illegal char")
-                    }
-                };
-                row.push(tile);
-            }
-            grid.push(row);
-        }
-        Self {
-            bound: Pos { x: w, y: h },
-            dir,
-            guard,
-            grid,
-        }
+    fn new(input: &Input) -> Result<Self> {
+        let chars = input.grid_with(|c| match c {
+            '.' | '#' => Ok(c),
+            // unsure if guard can be any of these initially, my challenge has it facing up:
+            _ if Dir::from_char(c).is_some() => Ok(c),
+            _ => Err(format!("invalid tile {:?}", c)),
+        })?;
+        let (guard, dir) = chars
+            .iter()
+            .find_map(|(p, c)| Some((p, Dir::from_char(*c)?)))
+            .ok_or_else(|| anyhow!("no guard"))?;
+        let grid = chars.map(|c| match c {
+            '.' => Tile::Empty,
+            '#' => Tile::Obst,
+            _ => Tile::Visited,
+        });
+        Ok(Self { dir, guard, grid })
     }
 
-    pub fn get_mut(&mut self, p: Pos) -> Option<&mut Tile> {
-        if p.x > self.bound.x || p.y > self.bound.y {
-            None
-        } else {
-            Some(&mut self.grid[p.y][p.x])
-        }
-    }
-
-    pub fn next_move(&self) -> Option<Pos> {
-        match self.dir {
-            Dir::Up => {
-                if self.guard.y == 0 {
-                    return None;
-                }
-                Some(Pos {
-                    x: self.guard.x,
-                    y: self.guard.y - 1,
-                })
-            }
-            Dir::Down => {
-                if self.guard.y == self.bound.y - 1 {
-                    return None;
-                }
-                Some(Pos {
-                    x: self.guard.x,
-                    y: self.guard.y + 1,
-                })
-            }
-            Dir::Left => {
-                if self.guard.x == 0 {
-                    return None;
-                }
-                Some(Pos {
-                    x: self.guard.x - 1,
-                    y: self.guard.y,
-                })
-            }
-            Dir::Right => {
-                if self.guard.x == self.bound.x - 1 {
-                    return None;
-                }
-                Some(Pos {
-                    x: self.guard.x + 1,
-                    y: self.guard.y,
-                })
-            }
-        }
+    pub fn next_move(&self) -> Option<Point> {
+        self.grid.go(self.guard, self.dir)
     }
 
     pub fn simulate(&mut self) -> bool {
@@ -145,7 +58,7 @@ impl Map {
             Some(p) => p,
             None => return false,
         };
-        if let Some(tile) = self.get_mut(next_pos) {
+        if let Some(tile) = self.grid.get_mut(next_pos) {
             match *tile {
                 Tile::Empty | Tile::Visited => {
                     *tile = Tile::Visited;
@@ -155,37 +68,30 @@ impl Map {
                     self.dir = self.dir.cw();
                 }
             }
-            return true;
+            true
         } else {
-            return false;
+            false
         }
     }
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut map = Map::new(lines);
+pub fn part1(input: &Input) -> Result<String> {
+    let mut map = parsing(|| Map::new(input))?;
     while map.simulate() {
-        print!(".")
+        trace!("guard at {} facing {:?}", map.guard, map.dir);
     }
-    let total: usize = map
+    let total = map
         .grid
-        .into_iter()
-        .map(|row| {
-            row.into_iter().fold(0, |acc, tile| {
-                acc + match tile {
-                    Tile::Visited => 1,
-                    _ => 0,
-                }
-            })
-        })
-        .sum();
+        .iter()
+        .filter(|(_, tile)| matches!(tile, Tile::Visited))
+        .count();
     Ok(total.to_string())
 }
 
 fn is_loopable(reality: &Map) -> bool {
     if let Some(next_pos) = reality.next_move() {
         let mut alt = reality.clone();
-        if let Some(tile) = alt.get_mut(next_pos) {
+        if let Some(tile) = alt.grid.get_mut(next_pos) {
             // warp in obstruction in front of guard iff Empty
             match *tile {
                 Tile::Empty => {
@@ -211,16 +117,14 @@ fn is_loopable(reality: &Map) -> bool {
     false
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut reality = Map::new(lines);
+pub fn part2(input: &Input) -> Result<String> {
+    let mut reality = parsing(|| Map::new(input))?;
     let mut total: usize = 0;
     loop {
         // Check if obstructing the guard at this point in time would result in a loop:
         if is_loopable(&reality) {
-            print!("@");
+            trace!("blocking the guard at {} makes a loop", reality.guard);
             total += 1;
-        } else {
-            print!(".");
         }
         // Carry on with the illusion of a linear reality:
         if !reality.simulate() {
@@ -230,6 +134,21 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
+pub struct Day06;
+impl Solver for Day06 {
+    fn day(&self) -> u8 {
+        6
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -239,7 +158,7 @@ mod test {
 
     #[test]
     fn test_part1() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             ....#.....
             .........#
             ..........
@@ -250,18 +169,15 @@ mod test {
             ........#.
             #.........
             ......#...
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
+        "});
 
-        assert_eq!(part1(input)?, "41");
+        assert_eq!(part1(&input)?, "41");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        let input: Vec<String> = indoc! {"
+        let input = Input::new(indoc! {"
             ....#.....
             .........#
             ..........
@@ -272,12 +188,9 @@ mod test {
             ........#.
             #.........
             ......#...
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect();
+        "});
 
-        assert_eq!(part2(input)?, "6");
+        assert_eq!(part2(&input)?, "6");
         Ok(())
     }
 }
@@ -1,14 +1,19 @@
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, Result};
 
-fn parse(line: String) -> Result<(usize, Vec<usize>)> {
-    let (cal_str, rest_str) = line.split_once(':').ok_or_else(|| anyhow!("no colon"))?;
-    let cal = cal_str.parse::<usize>()?;
+use crate::common::{Input, Line};
+use crate::solver::{parsing, Params, Solver};
+
+fn parse(line: Line) -> Result<(usize, Vec<usize>)> {
+    let (cal_str, rest_str) = line.split_once(":", "`test value: numbers`")?;
+    let cal = line.parse(cal_str)?;
     let mut nums: Vec<usize> = Vec::new();
     for x in rest_str.split_whitespace() {
-        nums.push(x.parse::<usize>()?)
+        nums.push(line.parse(x)?)
     }
     if nums.len() < 2 {
-        bail!("too short!")
+        return Err(line
+            .error_in(rest_str, "expected at least two numbers")
+            .into());
     }
     Ok((cal, nums))
 }
@@ -18,8 +23,8 @@ fn cat_base10(x: usize, y: usize) -> usize {
     y + x * 10_usize.pow(exp + 1)
 }
 
-fn check_calibration(line: String, part_two: bool) -> Result<usize> {
-    let (cal, nums) = parse(line)?;
+fn check_calibration(line: Line, part_two: bool) -> Result<usize> {
+    let (cal, nums) = parsing(|| parse(line))?;
 
     // accumulation of prior values:
     let mut acc: Vec<usize> = {
@@ -45,30 +50,45 @@ fn check_calibration(line: String, part_two: bool) -> Result<usize> {
     })
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
+pub fn part1(input: &Input) -> Result<String> {
     let mut total: usize = 0;
-    for line in lines {
+    for line in input.numbered_lines() {
         total += check_calibration(line, false)?;
     }
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
+pub fn part2(input: &Input) -> Result<String> {
     let mut total: usize = 0;
-    for line in lines {
+    for line in input.numbered_lines() {
         total += check_calibration(line, true)?;
     }
     Ok(total.to_string())
 }
 
+pub struct Day07;
+impl Solver for Day07 {
+    fn day(&self) -> u8 {
+        7
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
     use indoc::indoc;
 
     use super::*;
-    fn input() -> Vec<String> {
-        indoc! {"
+    fn input() -> Input {
+        Input::new(indoc! {"
             190: 10 19
             3267: 81 40 27
             83: 17 5
@@ -78,15 +98,12 @@ mod test {
             192: 17 8 14
             21037: 9 7 18 13
             292: 11 6 16 20
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect()
+        "})
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(input())?, "3749");
+        assert_eq!(part1(&input())?, "3749");
         Ok(())
     }
 
@@ -103,7 +120,7 @@ mod test {
 
     #[test]
     fn test_part2() -> Result<()> {
-        assert_eq!(part2(input())?, "11387");
+        assert_eq!(part2(&input())?, "11387");
         Ok(())
     }
 }
@@ -1,5 +1,7 @@
-use crate::common::Pos;
+use crate::common::{Input, Pos};
+use crate::solver::{parsing, Params, Solver};
 use anyhow::Result;
+use log::trace;
 use std::collections::{HashMap, HashSet};
 
 type Point = Pos<isize>;
@@ -14,43 +16,49 @@ impl Map {
     }
 }
 
-fn parse(lines: Vec<String>) -> Result<Map> {
+fn parse(input: &Input) -> Result<Map> {
+    let grid = input.grid_with(|c| {
+        if c == '.' || c.is_ascii_alphanumeric() {
+            Ok(c)
+        } else {
+            Err(format!("invalid antenna frequency {:?}", c))
+        }
+    })?;
+    let bounds = grid.bounds();
     let mut map = Map {
         nodes: HashMap::new(),
-        bound: Pos {
-            x: lines[0].len().try_into()?,
-            y: lines.len().try_into()?,
-        },
+        bound: (bounds.x, bounds.y).try_into()?,
     };
-    for (y, line) in lines.into_iter().enumerate() {
-        for (x, f) in line.chars().enumerate() {
-            if f == '.' {
-                continue;
-            }
-            let p = (x, y).try_into()?;
-            map.nodes.entry(f).or_insert(Vec::new()).push(p);
+    for (p, &f) in grid.iter() {
+        if f == '.' {
+            continue;
         }
+        map.nodes.entry(f).or_default().push((p.x, p.y).try_into()?);
     }
     Ok(map)
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
+pub fn part1(input: &Input) -> Result<String> {
     let mut antinodes = HashSet::new();
-    let map = parse(lines)?;
-    for (_f, poses) in &map.nodes {
+    let map = parsing(|| parse(input))?;
+    for (f, poses) in &map.nodes {
         for i in 0..poses.len() - 1 {
             // for some initial position
             let pi = poses[i];
-            for j in i + 1..poses.len() {
+            for &pj in &poses[i + 1..] {
                 // calculate antinodes with positions after it in the list
-                let pj = poses[j];
                 let d = pi - pj;
                 let a1 = pi + d;
                 let a2 = pj - d;
-                // println!(
-                //     "f {}: pi {:?}, pj {:?}, d {:?}, a1 {:?}, a2 {:?}",
-                //     _f, pi, pj, d, a1, a2
-                // );
+                trace!(
+                    "f {}: pi {:?}, pj {:?}, d {:?}, a1 {:?}, a2 {:?}",
+                    f,
+                    pi,
+                    pj,
+                    d,
+                    a1,
+                    a2
+                );
                 if map.in_bounds(&a1) {
                     antinodes.insert(a1);
                 }
@@ -60,13 +68,13 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
             }
         }
     }
-    Ok(antinodes.into_iter().count().to_string())
+    Ok(antinodes.len().to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
+pub fn part2(input: &Input) -> Result<String> {
     let mut antinodes = HashSet::new();
-    let map = parse(lines)?;
-    for (_f, poses) in &map.nodes {
+    let map = parsing(|| parse(input))?;
+    for (f, poses) in &map.nodes {
         if poses.len() < 2 {
             // no resonance possible
             continue;
@@ -75,9 +83,8 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
             // for some initial position
             let pi = poses[i];
             antinodes.insert(pi);
-            for j in (i + 1)..poses.len() {
+            for &pj in &poses[i + 1..] {
                 // calculate antinodes with positions after it in the list
-                let pj = poses[j];
                 antinodes.insert(pj);
 
                 let d = pi - pj;
@@ -85,19 +92,34 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
                 let mut a1 = pi + d;
                 while map.in_bounds(&a1) {
                     antinodes.insert(a1);
-                    // println!("f {}: {:?}", _f, a1);
+                    trace!("f {}: {:?}", f, a1);
                     a1 = a1 + d;
                 }
                 let mut a2 = pj - d;
                 while map.in_bounds(&a2) {
                     antinodes.insert(a2);
-                    // println!("f {}: {:?}", _f, a2);
+                    trace!("f {}: {:?}", f, a2);
                     a2 = a2 - d;
                 }
             }
         }
     }
-    Ok(antinodes.into_iter().count().to_string())
+    Ok(antinodes.len().to_string())
+}
+
+pub struct Day08;
+impl Solver for Day08 {
+    fn day(&self) -> u8 {
+        8
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -106,8 +128,8 @@ mod test {
     use indoc::indoc;
 
     use super::*;
-    fn input() -> Vec<String> {
-        indoc! {"
+    fn input() -> Input {
+        Input::new(indoc! {"
             ............
             ........0...
             .....0......
@@ -120,21 +142,18 @@ mod test {
             .........A..
             ............
             ............
-        "}
-        .lines()
-        .map(|x| x.to_string())
-        .collect()
+        "})
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(input())?, "14");
+        assert_eq!(part1(&input())?, "14");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        assert_eq!(part2(input())?, "34");
+        assert_eq!(part2(&input())?, "34");
         Ok(())
     }
 }
@@ -1,6 +1,10 @@
 use std::collections::BTreeSet;
 
 use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+use crate::common::Input;
+use crate::solver::{parsing, Params, Solver};
 
 #[derive(Debug, Clone)]
 struct FileBlocks {
@@ -13,28 +17,26 @@ struct Map {
     alloc: Vec<FileBlocks>,
 }
 
-fn parse(lines: Vec<String>) -> Result<Map> {
+fn parse(input: &Input) -> Result<Map> {
     // Single very-long line, RLE pairs
     let mut free: BTreeSet<usize> = BTreeSet::new();
     let mut alloc: Vec<FileBlocks> = Vec::new();
     let mut position: usize = 0;
-    let mut substrs = lines
-        .first()
-        .ok_or_else(|| anyhow!("missing line??"))?
-        .split_terminator("")
-        .skip(1); // split_terminator + skip will strip the first/last "" literals
-    loop {
-        if let Some(file_len_c) = substrs.next() {
-            let file_len: usize = file_len_c.to_string().parse()?;
-            let positions: Vec<usize> = (position..position + file_len).collect();
-            position += file_len;
-            alloc.push(FileBlocks { positions });
-        } else {
-            break;
-        }
+    let line = input.line()?;
+    let mut lens = Vec::new();
+    for (x, c) in line.text.chars().enumerate() {
+        let len = c
+            .to_digit(10)
+            .ok_or_else(|| line.error_at(x, format!("expected a digit, got {:?}", c)))?;
+        lens.push(len as usize);
+    }
+    let mut lens = lens.into_iter();
+    while let Some(file_len) = lens.next() {
+        let positions: Vec<usize> = (position..position + file_len).collect();
+        position += file_len;
+        alloc.push(FileBlocks { positions });
 
-        if let Some(free_len_c) = substrs.next() {
-            let mut free_len: usize = free_len_c.to_string().parse()?;
+        if let Some(mut free_len) = lens.next() {
             while free_len > 0 {
                 free.insert(position);
                 position += 1;
@@ -58,9 +60,9 @@ fn checksum(alloc: Vec<FileBlocks>) -> usize {
     total
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut map = parse(lines)?;
-    // println!("before: {:?}", map);
+pub fn part1(input: &Input) -> Result<String> {
+    let mut map = parsing(|| parse(input))?;
+    debug!("before: {:?}", map);
     map.alloc.reverse();
 
     {
@@ -85,26 +87,33 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
     }
 
     map.alloc.reverse();
-    // println!("after: {:?}", map.alloc);
+    debug!("after: {:?}", map.alloc);
     let total = checksum(map.alloc);
     Ok(total.to_string())
 }
 
 fn take_contiguous(free: &mut BTreeSet<usize>, need: usize, limit: usize) -> Option<Vec<usize>> {
     let mut found: Option<usize> = None;
-    if free.len() == 0 { return None }
+    if free.is_empty() {
+        return None;
+    }
     if need == 1 {
         // fast case for single-width hole
         if let Some(first) = free.pop_first() {
-            if first < limit { return Some(vec![first]) }
-            else { return None }
-        } else { return None }
+            if first < limit {
+                return Some(vec![first]);
+            } else {
+                return None;
+            }
+        } else {
+            return None;
+        }
     }
     {
         let mut iter = free.iter(); // closure for iterator
         let mut prev = *(iter.next().expect("at least one element needed"));
         let mut size: usize = 1;
-        while let Some(next) = iter.next() {
+        for next in iter {
             let cur = *next;
             if cur > limit {
                 // prevent forward relocations. Past the point of no-return
@@ -115,9 +124,9 @@ fn take_contiguous(free: &mut BTreeSet<usize>, need: usize, limit: usize) -> Opt
             } else {
                 size = 1; // restart
             }
-            
+
             if size == need {
-                found = Some(cur - (size-1));
+                found = Some(cur - (size - 1));
                 break;
             }
 
@@ -125,7 +134,7 @@ fn take_contiguous(free: &mut BTreeSet<usize>, need: usize, limit: usize) -> Opt
         }
     }
     if let Some(found) = found {
-        let taken: Vec<usize> = (found .. (found+need)).collect();
+        let taken: Vec<usize> = (found..(found + need)).collect();
         for take in taken.iter() {
             free.remove(take);
         }
@@ -134,16 +143,19 @@ fn take_contiguous(free: &mut BTreeSet<usize>, need: usize, limit: usize) -> Opt
     None
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut map = parse(lines)?;
-    // println!("before: {:?}", map);
+pub fn part2(input: &Input) -> Result<String> {
+    let mut map = parsing(|| parse(input))?;
+    debug!("before: {:?}", map);
 
-    println!("before free {}", map.free.len());
+    debug!("before free {}", map.free.len());
     for file in map.alloc.iter_mut().rev() {
-        // println!("prior: {:?}", file);
+        trace!("prior: {:?}", file);
         let need = file.positions.len();
 
-        let limit = file.positions.first().ok_or_else(|| anyhow!("zero width file??"))?;
+        let limit = file
+            .positions
+            .first()
+            .ok_or_else(|| anyhow!("zero width file??"))?;
         if let Some(mut relocations) = take_contiguous(&mut map.free, need, *limit) {
             assert_eq!(file.positions.len(), relocations.len());
             std::mem::swap(&mut file.positions, &mut relocations);
@@ -152,36 +164,49 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
                 map.free.insert(pos);
             }
         }
-        // println!("  after: {:?}", file);
+        debug!("  after: {:?}", file);
     }
 
-    // println!("after: {:?}", map.alloc);
-    println!("after free {}", map.free.len());
+    debug!("after: {:?}", map.alloc);
+    debug!("after free {}", map.free.len());
     let total = checksum(map.alloc);
     Ok(total.to_string())
 }
 
+pub struct Day09;
+impl Solver for Day09 {
+    fn day(&self) -> u8 {
+        9
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
 
     use super::*;
-    
-    fn ez_input() -> Vec<String> {
-        "12345".lines().map(|x| x.to_string()).collect()
+
+    fn ez_input() -> Input {
+        Input::new("12345")
     }
 
-    fn input() -> Vec<String> {
-        "2333133121414131402"
-            .lines()
-            .map(|x| x.to_string())
-            .collect()
+    fn input() -> Input {
+        Input::new("2333133121414131402")
     }
 
     #[test]
+    #[allow(clippy::identity_op, clippy::erasing_op)]
     fn test_part1_ez() -> Result<()> {
         assert_eq!(
-            part1(ez_input())?,
+            part1(&ez_input())?,
             (0 * 0 + 1 * 2 + 2 * 2 + 3 * 1 + 4 * 1 + 5 * 1 + 6 * 2 + 7 * 2 + 8 * 2).to_string()
         );
         Ok(())
@@ -189,13 +214,13 @@ mod test {
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(input())?, "1928");
+        assert_eq!(part1(&input())?, "1928");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        assert_eq!(part2(input())?, "2858");
+        assert_eq!(part2(&input())?, "2858");
         Ok(())
     }
 }
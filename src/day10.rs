@@ -1,6 +1,8 @@
-use crate::common::{Dir, Pos};
-use anyhow::{bail, Result};
+use crate::common::{Dir, Input, Pos};
+use crate::solver::{parsing, Params, Solver};
+use anyhow::Result;
 use enum_iterator;
+use log::{debug, trace};
 use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
@@ -8,19 +10,23 @@ struct Map {
     layers: Vec<Vec<Pos<isize>>>,
 }
 
-fn parse(lines: Vec<String>) -> Result<Map> {
+fn parse(input: &Input) -> Result<Map> {
     let mut map = Map { layers: Vec::new() };
     for _ in 0..=10 {
         map.layers.push(Vec::new());
     }
 
-    for (y, line) in lines.into_iter().enumerate() {
-        for (x, c) in line.chars().enumerate() {
+    for (y, line) in input.numbered_lines().enumerate() {
+        for (x, c) in line.text.chars().enumerate() {
             let p = (x, y).try_into()?;
             let z = match c {
                 '.' => 10, // "unreachable"
                 '0'..='9' => c.to_digit(10).expect("decimal digit"),
-                c => bail!("invalid map character {}", c),
+                c => {
+                    return Err(line
+                        .error_at(x, format!("invalid map character {:?}", c))
+                        .into())
+                }
             } as usize;
             map.layers[z].push(p);
         }
@@ -37,16 +43,16 @@ fn peak_closure(map: &Map) -> usize {
     }
 
     for z in (0..=8).rev() {
-        // println!(" above {}: {:?}", z, above);
+        trace!(" above {}: {:?}", z, above);
         let mut current = HashMap::new();
         for p1 in &map.layers[z] {
-            // println!("  consider {:?}", p1);
+            trace!("  consider {:?}", p1);
             let mut reach = HashSet::new();
             for dir in enum_iterator::all::<Dir>() {
                 let p2 = p1.go(dir);
-                // println!("   then {:?}", p2);
+                trace!("   then {:?}", p2);
                 if let Some(reach2) = above.get(&p2) {
-                    // println!("    ok {:?} has {:?}", p2, reach2);
+                    trace!("    ok {:?} has {:?}", p2, reach2);
                     for r in reach2 {
                         reach.insert(*r);
                     }
@@ -54,11 +60,11 @@ fn peak_closure(map: &Map) -> usize {
             }
             current.insert(*p1, reach);
         }
-        // println!(" at {}: {:?}", z, current);
+        trace!(" at {}: {:?}", z, current);
         above = current;
     }
 
-    // println!("at end {:?}", above);
+    debug!("at end {:?}", above);
     let mut total = 0;
     for p in &map.layers[0] {
         if let Some(reach) = above.get(p) {
@@ -70,8 +76,8 @@ fn peak_closure(map: &Map) -> usize {
     total
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let map = parsing(|| parse(input))?;
     let total = peak_closure(&map);
     Ok(total.to_string())
 }
@@ -100,12 +106,27 @@ fn pathways(map: &Map) -> usize {
     above.into_values().sum()
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
+pub fn part2(input: &Input) -> Result<String> {
+    let map = parsing(|| parse(input))?;
     let total = pathways(&map);
     Ok(total.to_string())
 }
 
+pub struct Day10;
+impl Solver for Day10 {
+    fn day(&self) -> u8 {
+        10
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -113,10 +134,6 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1_a() -> Result<()> {
         let text = indoc! {"
@@ -125,7 +142,7 @@ mod test {
             8765
             9876
         "};
-        assert_eq!(part1(lines(text))?, "1");
+        assert_eq!(part1(&Input::new(text))?, "1");
         Ok(())
     }
 
@@ -140,7 +157,7 @@ mod test {
             8.....8
             9.....9
         "};
-        assert_eq!(part1(lines(text))?, "2");
+        assert_eq!(part1(&Input::new(text))?, "2");
         Ok(())
     }
 
@@ -155,7 +172,7 @@ mod test {
             876....
             987....
         "};
-        assert_eq!(part1(lines(text))?, "4");
+        assert_eq!(part1(&Input::new(text))?, "4");
         Ok(())
     }
 
@@ -170,7 +187,7 @@ mod test {
             ...9..2
             .....01
         "};
-        assert_eq!(part1(lines(text))?, "3");
+        assert_eq!(part1(&Input::new(text))?, "3");
         Ok(())
     }
 
@@ -186,7 +203,7 @@ mod test {
             01329801
             10456732
         "};
-        assert_eq!(part1(lines(text))?, "36");
+        assert_eq!(part1(&Input::new(text))?, "36");
         Ok(())
     }
 
@@ -201,7 +218,7 @@ mod test {
             ..8765.
             ..9....
         "};
-        assert_eq!(part2(lines(text))?, "3");
+        assert_eq!(part2(&Input::new(text))?, "3");
         Ok(())
     }
 
@@ -216,7 +233,7 @@ mod test {
             876....
             987....
         "};
-        assert_eq!(part2(lines(text))?, "13");
+        assert_eq!(part2(&Input::new(text))?, "13");
         Ok(())
     }
 
@@ -230,7 +247,7 @@ mod test {
             4.6789
             56789.
         "};
-        assert_eq!(part2(lines(text))?, "227");
+        assert_eq!(part2(&Input::new(text))?, "227");
         Ok(())
     }
 
@@ -246,7 +263,7 @@ mod test {
             01329801
             10456732
         "};
-        assert_eq!(part2(lines(text))?, "81");
+        assert_eq!(part2(&Input::new(text))?, "81");
         Ok(())
     }
 }
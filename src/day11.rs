@@ -1,12 +1,14 @@
-use anyhow::{anyhow, Result};
+use anyhow::{bail, Result};
 use std::collections::HashMap;
 
-fn parse(lines: Vec<String>) -> Result<Vec<usize>> {
-    let line = lines.first().ok_or_else(|| anyhow!("no lines?"))?;
-    let stones = line
-        .split_whitespace()
-        .map(|s| s.parse().expect("int"))
-        .collect();
+use crate::common::Input;
+use crate::solver::{parsing, Param, Params, Solver};
+
+fn parse(input: &Input) -> Result<Vec<usize>> {
+    let stones = input.numbers()?;
+    if stones.is_empty() {
+        bail!("no stones");
+    }
     Ok(stones)
 }
 
@@ -16,7 +18,7 @@ fn blink(s: usize) -> (usize, Option<usize>) {
     }
 
     let digits = s.checked_ilog10().unwrap_or(0) + 1;
-    if digits % 2 == 0 {
+    if digits.is_multiple_of(2) {
         let d = 10_usize.pow(digits >> 1);
         (s / d, Some(s % d))
     } else {
@@ -58,26 +60,58 @@ fn blink_n(s: usize, blinks: usize, memo: &mut HashMap<(usize, usize), usize>) -
     count
 }
 
-pub fn part1(lines: Vec<String>, blinks: usize) -> Result<String> {
-    let mut stones = parse(lines)?;
+pub fn part1(input: &Input, blinks: usize) -> Result<String> {
+    let mut stones = parsing(|| parse(input))?;
     for _ in 0..blinks {
         stones = blink_all(stones)?;
     }
-    let total: usize = stones.into_iter().count();
+    let total: usize = stones.len();
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>, blinks: usize) -> Result<String> {
+pub fn part2(input: &Input, blinks: usize) -> Result<String> {
     let mut memo = HashMap::new();
 
     let mut total = 0;
-    for stone in parse(lines)?.into_iter() {
+    for stone in parsing(|| parse(input))?.into_iter() {
         total += blink_n(stone, blinks, &mut memo);
     }
 
     Ok(total.to_string())
 }
 
+pub struct Day11;
+impl Solver for Day11 {
+    fn day(&self) -> u8 {
+        11
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, params.get("blinks1")?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, params.get("blinks2")?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "blinks1",
+                default: "25",
+                help: "blinks for part 1",
+                check: Param::parses::<usize>,
+            },
+            Param {
+                key: "blinks2",
+                default: "75",
+                help: "blinks for part 2",
+                check: Param::parses::<usize>,
+            },
+        ]
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -86,17 +120,13 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
-    fn ints_to_str(stones: &Vec<usize>) -> String {
-        stones.into_iter().map(|s| s.to_string()).join(" ")
+    fn ints_to_str(stones: &[usize]) -> String {
+        stones.iter().map(|s| s.to_string()).join(" ")
     }
 
     #[test]
     fn test_part1_a() -> Result<()> {
-        let stones = parse(vec!["0 1 10 99 999".to_owned()])?;
+        let stones = parse(&Input::new("0 1 10 99 999"))?;
         let result = blink_all(stones)?;
         assert_eq!(ints_to_str(&result), "1 2024 1 0 9 9 2021976");
         Ok(())
@@ -104,7 +134,7 @@ mod test {
 
     #[test]
     fn test_part1_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             253000 1 7
             253 0 2024 14168
             512072 1 20 24 28676032
@@ -112,9 +142,9 @@ mod test {
             1036288 7 2 20 24 4048 1 4048 8096 28 67 60 32
             2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
         "});
-        let mut stones = parse(vec!["125 17".to_owned()])?;
+        let mut stones = parse(&Input::new("125 17"))?;
 
-        for expect in lines {
+        for expect in input.lines() {
             stones = blink_all(stones)?;
             assert_eq!(ints_to_str(&stones), expect);
         }
@@ -123,17 +153,17 @@ mod test {
 
     #[test]
     fn test_part2_a() -> Result<()> {
-        assert_eq!(part2(vec!["0 1 10 99 999".to_owned()], 1)?, "7");
+        assert_eq!(part2(&Input::new("0 1 10 99 999"), 1)?, "7");
         Ok(())
     }
 
     #[test]
     fn test_part2_b() -> Result<()> {
-        assert_eq!(part2(vec!["253000 1 7".to_owned()], 1)?, "4");
-        assert_eq!(part2(vec!["253000 1 7".to_owned()], 2)?, "5");
-        assert_eq!(part2(vec!["253000 1 7".to_owned()], 3)?, "9");
-        assert_eq!(part2(vec!["253000 1 7".to_owned()], 4)?, "13");
-        assert_eq!(part2(vec!["253000 1 7".to_owned()], 5)?, "22");
+        assert_eq!(part2(&Input::new("253000 1 7"), 1)?, "4");
+        assert_eq!(part2(&Input::new("253000 1 7"), 2)?, "5");
+        assert_eq!(part2(&Input::new("253000 1 7"), 3)?, "9");
+        assert_eq!(part2(&Input::new("253000 1 7"), 4)?, "13");
+        assert_eq!(part2(&Input::new("253000 1 7"), 5)?, "22");
         Ok(())
     }
 }
@@ -1,76 +1,66 @@
 use std::collections::HashSet;
 
-use crate::common::{graphrs_anyhow, Dir, Pos};
-use anyhow::{anyhow, Result};
-use graphrs::{algorithms::components::connected_components, Graph, GraphSpecs, Node};
+use crate::common::search::bfs;
+use crate::common::{Dir, Grid, Input, Pos};
+use crate::solver::{parsing, Params, Solver};
+use anyhow::Result;
+use log::{debug, trace};
 
 type Point = Pos<usize>;
 
 struct Map {
-    plots: Vec<Vec<char>>,
-    bounds: Point,
+    plots: Grid<char>,
 }
 impl Map {
     pub fn go(&self, p: &Point, dir: Dir) -> Option<Point> {
-        p.go_bounded(dir, &self.bounds)
+        self.plots.go(*p, dir)
     }
 }
 
-fn map_to_graph(map: &Map) -> Result<Graph<Point, ()>> {
-    let mut g: Graph<Point, ()> = Graph::new(GraphSpecs::undirected());
-    for y in 0..map.bounds.y {
-        for x in 0..map.bounds.x {
-            let u = Point { x, y };
-            g.add_node(Node::from_name(u));
-        }
-    }
-    for y in 0..map.bounds.y {
-        for x in 0..map.bounds.x {
-            let u = Point { x, y };
-            let u_plant = map.plots[u.y][u.x];
-            if x > 0 {
-                let v = Point { x: x - 1, y };
-                let v_plant = map.plots[v.y][v.x];
-                if u_plant == v_plant {
-                    g.add_edge_tuple(u, v).map_err(graphrs_anyhow)?;
-                }
-            }
-            if y > 0 {
-                let v = Point { x, y: y - 1 };
-                let v_plant = map.plots[v.y][v.x];
-                if u_plant == v_plant {
-                    g.add_edge_tuple(u, v).map_err(graphrs_anyhow)?;
-                }
-            }
+/// Adjacent plots growing the same plant as `u`
+fn same_plant<'a>(map: &'a Map, u: &Point) -> impl Iterator<Item = Point> + 'a {
+    let plant = map.plots[*u];
+    map.plots
+        .neighbours(*u)
+        .map(|(_, v)| v)
+        .filter(move |v| map.plots[*v] == plant)
+}
+
+/// Connected regions of the same plant
+fn regions(map: &Map) -> Vec<HashSet<Point>> {
+    let mut seen: HashSet<Point> = HashSet::new();
+    let mut out = Vec::new();
+    for p in map.plots.positions() {
+        if seen.contains(&p) {
+            continue;
         }
+        let region: HashSet<Point> = bfs(p, |u| same_plant(map, u))
+            .costs()
+            .keys()
+            .copied()
+            .collect();
+        seen.extend(region.iter());
+        out.push(region);
     }
-    Ok(g)
+    out
 }
 
-fn parse(lines: Vec<String>) -> Result<Map> {
-    let plots: Vec<Vec<char>> = lines.into_iter().map(|row| row.chars().collect()).collect();
-    let bounds = Point {
-        x: plots.first().ok_or_else(|| anyhow!("empty map?"))?.len(),
-        y: plots.len(),
-    };
-    Ok(Map { plots, bounds })
+fn parse(input: &Input) -> Result<Map> {
+    let plots = input.grid()?;
+    Ok(Map { plots })
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
-    let g = map_to_graph(&map)?;
-
-    let cc = connected_components(&g).map_err(graphrs_anyhow)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let map = parsing(|| parse(input))?;
     let mut total = 0;
-    for component in cc {
-        // println!("component: {:?}", component);
+    for component in regions(&map) {
+        debug!("component: {:?}", component);
         let area = component.len();
         let mut fences = component.len() * 4;
         for p in component.iter() {
-            let edges = g.get_edges_for_node(*p).map_err(graphrs_anyhow)?;
-            fences -= edges.len();
+            fences -= same_plant(&map, p).count();
         }
-        // println!(" fences: {}", fences);
+        trace!(" fences: {}", fences);
         let cost = area * fences;
         total += cost;
     }
@@ -85,11 +75,11 @@ fn linear_flood(
     fenced: &HashSet<Point>,
     seen: &mut HashSet<Point>,
 ) {
-    let mut cursor = p.clone();
+    let mut cursor = *p;
     while let Some(p2) = map.go(&cursor, dir) {
-        // println!("  try: {:?} {:?}", dir, p2);
+        trace!("  try: {:?} {:?}", dir, p2);
         if fenced.contains(&p2) {
-            // println!("  extension: {:?} {:?}", dir, p2);
+            trace!("  extension: {:?} {:?}", dir, p2);
             seen.insert(p2);
             cursor = p2;
         } else {
@@ -98,14 +88,11 @@ fn linear_flood(
     }
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
-    let g = map_to_graph(&map)?;
-
-    let cc = connected_components(&g).map_err(graphrs_anyhow)?;
+pub fn part2(input: &Input) -> Result<String> {
+    let map = parsing(|| parse(input))?;
     let mut total = 0;
-    for component in cc {
-        // println!("component: {:?}", component);
+    for component in regions(&map) {
+        debug!("component: {:?}", component);
         let area = component.len();
         if area <= 2 {
             // single or double always a rectangle:
@@ -116,7 +103,7 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
         let mut sides = 0;
 
         for dir in enum_iterator::all::<Dir>() {
-            // println!(" dir {:?}", dir);
+            trace!(" dir {:?}", dir);
             let (ccw, cw) = dir.orthos();
             let mut seen = HashSet::new(); // "seen for this fence direction"
 
@@ -124,14 +111,14 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
                 HashSet::from_iter(component.iter().filter_map(|x| {
                     let beside = map.go(x, dir);
                     if beside.is_none() || !component.contains(&beside.unwrap()) {
-                        Some(x.clone())
+                        Some(*x)
                     } else {
                         None
                     }
                 }));
 
             for p in fenced.iter() {
-                if !seen.insert(p.clone()) {
+                if !seen.insert(*p) {
                     continue; // already excluded
                 }
 
@@ -141,7 +128,7 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
                 // Flood fill in orthogonal directions to include them in the
                 // side, i.e., exclude them from being detected as forming a new
                 // side.
-                // println!("  orthos: {:?} {:?}", ccw, cw);
+                trace!("  orthos: {:?} {:?}", ccw, cw);
                 linear_flood(&map, p, ccw, &fenced, &mut seen);
                 linear_flood(&map, p, cw, &fenced, &mut seen);
             }
@@ -152,6 +139,21 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
+pub struct Day12;
+impl Solver for Day12 {
+    fn day(&self) -> u8 {
+        12
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -159,35 +161,32 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_part1_ez() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             RR
             RA
         "});
-        assert_eq!(part1(lines)?, (3 * 8 + 1 * 4).to_string());
+        assert_eq!(part1(&input)?, (3 * 8 + 1 * 4).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part1_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             AAAA
             BBCD
             BBCC
             EEEC
         "});
-        assert_eq!(part1(lines)?, "140");
+        assert_eq!(part1(&input)?, "140");
         Ok(())
     }
 
     #[test]
     fn test_part1_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             RRRRIICCFF
             RRRRIICCCF
             VVRRRCCFFF
@@ -199,48 +198,49 @@ mod test {
             MIIISIJEEE
             MMMISSJEEE
         "});
-        assert_eq!(part1(lines)?, "1930");
+        assert_eq!(part1(&input)?, "1930");
         Ok(())
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_part2_ez() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             RR
             RA
         "});
-        assert_eq!(part2(lines)?, (3 * 6 + 1 * 4).to_string());
+        assert_eq!(part2(&input)?, (3 * 6 + 1 * 4).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part2_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             AAAA
             BBCD
             BBCC
             EEEC
         "});
-        assert_eq!(part2(lines)?, "80");
+        assert_eq!(part2(&input)?, "80");
         Ok(())
     }
 
     #[test]
     fn test_part2_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             EEEEE
             EXXXX
             EEEEE
             EXXXX
             EEEEE
         "});
-        assert_eq!(part2(lines)?, "236");
+        assert_eq!(part2(&input)?, "236");
         Ok(())
     }
 
     #[test]
     fn test_part2_c() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             AAAAAA
             AAABBA
             AAABBA
@@ -248,13 +248,13 @@ mod test {
             ABBAAA
             AAAAAA
         "});
-        assert_eq!(part2(lines)?, "368");
+        assert_eq!(part2(&input)?, "368");
         Ok(())
     }
 
     #[test]
     fn test_part2_d() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             RRRRIICCFF
             RRRRIICCCF
             VVRRRCCFFF
@@ -266,7 +266,7 @@ mod test {
             MIIISIJEEE
             MMMISSJEEE
         "});
-        assert_eq!(part2(lines)?, "1206");
+        assert_eq!(part2(&input)?, "1206");
         Ok(())
     }
 }
@@ -1,7 +1,7 @@
-use anyhow::{anyhow, Result};
-use regex::Regex;
+use anyhow::Result;
 
-use crate::common::Pos;
+use crate::common::{Input, Line, Pos};
+use crate::solver::{parsing, Param, Params, Solver};
 
 #[derive(Debug)]
 struct Challenge {
@@ -10,37 +10,34 @@ struct Challenge {
     prize: Pos<isize>,
 }
 
-fn re_parse_pos(haystack: &str, re: &Regex) -> Result<Pos<isize>> {
-    let cap = re
-        .captures(haystack)
-        .ok_or_else(|| anyhow!("re fail '{}' '{}'", re, haystack))?;
-    Ok(Pos {
-        x: (&cap[1]).parse()?,
-        y: (&cap[2]).parse()?,
-    })
+fn parse_pos(line: Line) -> Result<Pos<isize>> {
+    let [x, y] = line.numbers()?[..] else {
+        return Err(line.error("expected an X and a Y").into());
+    };
+    Ok(Pos { x, y })
 }
 
-fn parse(lines: Vec<String>) -> Result<Vec<Challenge>> {
-    let mut iter = lines.into_iter();
-    let re_button = Regex::new(r"^Button .: X\+([0-9]+), Y\+([0-9]+)").unwrap();
-    let re_prize = Regex::new(r"^Prize: X=([0-9]+), Y=([0-9]+)").unwrap();
+/// One machine per section: button A, button B, then the prize
+fn parse(input: &Input) -> Result<Vec<Challenge>> {
     let mut challenges = Vec::new();
-    loop {
-        let a = re_parse_pos(&iter.next().unwrap(), &re_button)?;
-        let b = re_parse_pos(&iter.next().unwrap(), &re_button)?;
-        let prize = re_parse_pos(&iter.next().unwrap(), &re_prize)?;
-        challenges.push(Challenge { a, b, prize });
-
-        if iter.next().is_none() {
-            break;
-        }
+    for section in input.sections() {
+        let lines: Vec<Line> = section.numbered_lines().collect();
+        let [a, b, prize] = lines[..] else {
+            return Err(lines[0]
+                .error("expected button A, button B and a prize")
+                .into());
+        };
+        challenges.push(Challenge {
+            a: parse_pos(a)?,
+            b: parse_pos(b)?,
+            prize: parse_pos(prize)?,
+        });
     }
-
     Ok(challenges)
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let chals = parse(lines)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let chals = parsing(|| parse(input))?;
     let mut solves: Vec<isize> = vec![isize::MAX; chals.len()];
     for (i, c) in chals.iter().enumerate() {
         for a_press in 0..=100 {
@@ -100,12 +97,12 @@ fn exact_solution(chal: &Challenge) -> Option<(isize, isize)> {
     }
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
+pub fn part2(input: &Input, offset: isize) -> Result<String> {
     let offset = Pos {
-        x: 10000000000000,
-        y: 10000000000000,
+        x: offset,
+        y: offset,
     };
-    let chals = parse(lines)?.into_iter().map(|c| Challenge {
+    let chals = parsing(|| parse(input))?.into_iter().map(|c| Challenge {
         a: c.a,
         b: c.b,
         prize: c.prize + offset,
@@ -124,6 +121,30 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
+pub struct Day13;
+impl Solver for Day13 {
+    fn day(&self) -> u8 {
+        13
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, params.get("offset")?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[Param {
+            key: "offset",
+            default: "10000000000000",
+            help: "added to the prize coordinates in part 2",
+            check: Param::parses::<isize>,
+        }]
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -131,13 +152,9 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             Button A: X+94, Y+34
             Button B: X+22, Y+67
             Prize: X=8400, Y=5400
@@ -154,13 +171,13 @@ mod test {
             Button B: X+27, Y+71
             Prize: X=18641, Y=10279
         "});
-        assert_eq!(part1(lines)?, "480");
+        assert_eq!(part1(&input)?, "480");
         Ok(())
     }
 
     #[test]
     fn test_part2_yes() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             Button A: X+26, Y+66
             Button B: X+67, Y+21
             Prize: X=12748, Y=12176
@@ -169,13 +186,13 @@ mod test {
             Button B: X+27, Y+71
             Prize: X=18641, Y=10279
         "});
-        assert_eq!(part2(lines)?, "875318608908");
+        assert_eq!(part2(&input, 10000000000000)?, "875318608908");
         Ok(())
     }
 
     #[test]
     fn test_part2_no() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             Button A: X+94, Y+34
             Button B: X+22, Y+67
             Prize: X=8400, Y=5400
@@ -184,7 +201,7 @@ mod test {
             Button B: X+84, Y+37
             Prize: X=7870, Y=6450
         "});
-        assert_eq!(part2(lines)?, "0");
+        assert_eq!(part2(&input, 10000000000000)?, "0");
         Ok(())
     }
 }
@@ -1,29 +1,17 @@
-use std::io;
-use std::time::Duration;
-
 use anyhow::{anyhow, Result};
-use crossterm::{
-    cursor,
-    event::{
-        poll, read, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent,
-        KeyEventKind,
-    },
-    execute, queue, style,
-    terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, DisableLineWrap, EnableLineWrap,
-        EnterAlternateScreen, LeaveAlternateScreen,
-    },
-};
+use log::{debug, trace};
 use regex::Regex;
 
-use crate::common::Pos;
+use crate::common::{Input, Pos};
+use crate::solver::{parsing, Param, Params, Solver};
 
+#[derive(Debug, Clone)]
 struct Bot {
-    p: Pos,
-    v: Pos,
+    p: Pos<isize>,
+    v: Pos<isize>,
 }
 impl Bot {
-    fn simulate(&mut self, bounds: Pos) {
+    fn simulate(&mut self, bounds: Pos<isize>) {
         let mut p = self.p + self.v;
         if p.x < 0 {
             p.x += bounds.x;
@@ -38,7 +26,8 @@ impl Bot {
         self.p = p;
     }
 
-    fn un_simulate(&mut self, bounds: Pos) {
+    #[cfg_attr(not(feature = "tui"), allow(dead_code))]
+    fn un_simulate(&mut self, bounds: Pos<isize>) {
         let mut p = self.p - self.v;
         if p.x < 0 {
             p.x += bounds.x;
@@ -52,30 +41,39 @@ impl Bot {
         }
         self.p = p;
     }
+
+    /// Position after `n` seconds, without stepping through them
+    fn at(&self, n: isize, bounds: Pos<isize>) -> Pos<isize> {
+        let p = self.p + self.v * n;
+        Pos {
+            x: p.x.rem_euclid(bounds.x),
+            y: p.y.rem_euclid(bounds.y),
+        }
+    }
 }
 
-fn parse(lines: Vec<String>) -> Result<Vec<Bot>> {
+fn parse(input: &Input) -> Result<Vec<Bot>> {
     let re = Regex::new(r"^p=(-?[0-9]+),(-?[0-9]+) v=(-?[0-9]+),(-?[0-9]+)").unwrap();
     let mut bots = Vec::new();
-    for line in lines {
+    for line in input.numbered_lines() {
         let cap = re
-            .captures(&line)
-            .ok_or_else(|| anyhow!("failed to parse line: {}", &line))?;
+            .captures(line.text)
+            .ok_or_else(|| line.error("expected `p=X,Y v=DX,DY`"))?;
         let p = Pos {
-            x: cap[1].parse()?,
-            y: cap[2].parse()?,
+            x: line.parse(&cap[1])?,
+            y: line.parse(&cap[2])?,
         };
         let v = Pos {
-            x: cap[3].parse()?,
-            y: cap[4].parse()?,
+            x: line.parse(&cap[3])?,
+            y: line.parse(&cap[4])?,
         };
         bots.push(Bot { p, v });
     }
     Ok(bots)
 }
 
-pub fn part1(lines: Vec<String>, bounds: Pos) -> Result<String> {
-    let mut bots = parse(lines)?;
+pub fn part1(input: &Input, bounds: Pos<isize>) -> Result<String> {
+    let mut bots = parsing(|| parse(input))?;
     let cycles = 100;
     for _ in 0..cycles {
         for b in bots.iter_mut() {
@@ -86,7 +84,7 @@ pub fn part1(lines: Vec<String>, bounds: Pos) -> Result<String> {
     let y_part = bounds.y / 2;
     let mut quads: [isize; 4] = [0, 0, 0, 0];
     for b in bots {
-        println!("{:?}", b.p);
+        trace!("{:?}", b.p);
         if b.p.x < x_part {
             if b.p.y < y_part {
                 quads[0] += 1;
@@ -101,155 +99,256 @@ pub fn part1(lines: Vec<String>, bounds: Pos) -> Result<String> {
             }
         }
     }
-    println!("{:?}", quads);
-    let total: isize = quads.into_iter().fold(1, |acc, x| acc * x);
+    debug!("{:?}", quads);
+    let total: isize = quads.into_iter().product();
     Ok(total.to_string())
 }
 
-fn part2_sim<W>(w: &mut W, bots: &mut Vec<Bot>, bounds: Pos) -> Result<String>
-where
-    W: io::Write,
-{
-    let min_dur = Duration::from_millis(50);
-    let mut dur = Duration::from_millis(250);
-    let mut n: usize = 0;
-    let at_a_time = 101; // noticed patterns at 23 + (k*101)
-    let mut simulating = false;
-    let mut forward = true;
-    let mut once = false;
-
-    queue!(w, Clear(ClearType::All),)?;
-
-    'outer: loop {
-        if poll(dur)? {
-            let event = read()?;
-            if let Event::Key(KeyEvent { code, kind, .. }) = event {
-                if kind == KeyEventKind::Press {
-                    match code {
-                        KeyCode::Backspace => break 'outer,
-                        KeyCode::Down => {
-                            dur = dur.checked_sub(Duration::from_millis(50)).unwrap_or(dur);
-                            if dur < min_dur {
-                                dur = min_dur;
+/// Variance of `xs`, scaled by n² to stay in integers
+fn spread(xs: impl Iterator<Item = isize>) -> isize {
+    let (mut n, mut sum, mut sq) = (0, 0, 0);
+    for x in xs {
+        n += 1;
+        sum += x;
+        sq += x * x;
+    }
+    n * sq - sum * sum
+}
+
+/// Finds the frame where the bots bunch up into the tree. Their x positions
+/// repeat every `bounds.x` seconds and y every `bounds.y`, so find the
+/// tightest x and y frames separately and combine them (Chinese remainder).
+fn find_tree(bots: &[Bot], bounds: Pos<isize>) -> Result<isize> {
+    let tightest = |period: isize, coord: fn(Pos<isize>) -> isize| {
+        (0..period)
+            .min_by_key(|&n| spread(bots.iter().map(|b| coord(b.at(n, bounds)))))
+            .ok_or_else(|| anyhow!("empty bounds"))
+    };
+    let tx = tightest(bounds.x, |p| p.x)?;
+    let ty = tightest(bounds.y, |p| p.y)?;
+    (0..bounds.y)
+        .map(|k| tx + k * bounds.x)
+        .find(|n| n % bounds.y == ty)
+        .ok_or_else(|| anyhow!("no frame fits both periods; bounds must be coprime"))
+}
+
+pub fn part2(input: &Input, bounds: Pos<isize>) -> Result<String> {
+    let bots = parsing(|| parse(input))?;
+    Ok(find_tree(&bots, bounds)?.to_string())
+}
+
+/// Interactive playback of part 2, for eyeballing the tree
+#[cfg(feature = "tui")]
+mod animation {
+    use std::io;
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use crossterm::{
+        cursor,
+        event::{
+            poll, read, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent,
+            KeyEventKind,
+        },
+        execute, queue, style,
+        terminal::{
+            disable_raw_mode, enable_raw_mode, Clear, ClearType, DisableLineWrap, EnableLineWrap,
+            EnterAlternateScreen, LeaveAlternateScreen,
+        },
+    };
+
+    use super::{parse, Bot};
+    use crate::common::{Input, Pos};
+
+    fn part2_sim<W>(w: &mut W, bots: &mut [Bot], bounds: Pos<isize>) -> Result<String>
+    where
+        W: io::Write,
+    {
+        let min_dur = Duration::from_millis(50);
+        let mut dur = Duration::from_millis(250);
+        let mut n: usize = 0;
+        let at_a_time = 101; // noticed patterns at 23 + (k*101)
+        let mut simulating = false;
+        let mut forward = true;
+        let mut once = false;
+
+        queue!(w, Clear(ClearType::All),)?;
+
+        'outer: loop {
+            if poll(dur)? {
+                let event = read()?;
+                if let Event::Key(KeyEvent { code, kind, .. }) = event {
+                    if kind == KeyEventKind::Press {
+                        match code {
+                            KeyCode::Backspace => break 'outer,
+                            KeyCode::Down => {
+                                dur = dur.checked_sub(Duration::from_millis(50)).unwrap_or(dur);
+                                if dur < min_dur {
+                                    dur = min_dur;
+                                }
                             }
-                        }
-                        KeyCode::Up => {
-                            dur = dur.checked_add(Duration::from_millis(50)).unwrap_or(dur);
-                        }
-                        KeyCode::Left => {
-                            forward = false;
-                            once = true;
-                        }
-                        KeyCode::Right => {
-                            forward = true;
-                            once = true;
-                        }
-                        KeyCode::Char(c) => match c {
-                            ' ' => simulating = !simulating,
+                            KeyCode::Up => {
+                                dur = dur.checked_add(Duration::from_millis(50)).unwrap_or(dur);
+                            }
+                            KeyCode::Left => {
+                                forward = false;
+                                once = true;
+                            }
+                            KeyCode::Right => {
+                                forward = true;
+                                once = true;
+                            }
+                            KeyCode::Char(' ') => simulating = !simulating,
                             _ => {}
-                        },
-                        _ => {}
+                        }
                     }
                 }
             }
-        }
 
-        if simulating || once {
-            let times = if once { 1 } else { at_a_time };
-            for _ in 0..times {
-                for b in bots.iter_mut() {
-                    if forward {
-                        b.simulate(bounds);
-                    } else {
-                        if n >= 1 {
+            if simulating || once {
+                let times = if once { 1 } else { at_a_time };
+                for _ in 0..times {
+                    for b in bots.iter_mut() {
+                        if forward {
+                            b.simulate(bounds);
+                        } else if n >= 1 {
                             b.un_simulate(bounds);
                         }
                     }
-                }
 
-                if forward {
-                    n += 1;
-                } else {
-                    if n >= 1 {
+                    if forward {
+                        n += 1;
+                    } else if n >= 1 {
                         n -= 1;
                     }
                 }
-            }
 
-            once = false;
-        }
+                once = false;
+            }
 
-        queue!(w, style::ResetColor, cursor::Hide, cursor::MoveTo(0, 0),)?;
+            queue!(w, style::ResetColor, cursor::Hide, cursor::MoveTo(0, 0),)?;
 
-        let mut lines = Vec::new();
-        for _ in 0..bounds.y {
-            lines.push(vec!['.'; bounds.x as usize]);
-        }
-        for b in bots.iter() {
-            lines[b.p.y as usize][b.p.x as usize] = '█';
-        }
+            let mut lines = Vec::new();
+            for _ in 0..bounds.y {
+                lines.push(vec!['.'; bounds.x as usize]);
+            }
+            for b in bots.iter() {
+                lines[b.p.y as usize][b.p.x as usize] = '█';
+            }
 
-        queue!(
-            w,
-            style::SetBackgroundColor(if simulating {
-                style::Color::DarkGreen
-            } else {
-                style::Color::DarkRed
-            }),
-            style::SetForegroundColor(style::Color::White),
-            style::Print(format!(
-                "Iteration: {}{} @ {}ms             ",
-                n,
-                if forward { "++" } else { "--" },
-                dur.as_millis()
-            )),
-            cursor::MoveToNextLine(1),
-            style::ResetColor,
-        )?;
-        for chars in lines {
-            let line: String = chars.into_iter().collect();
             queue!(
                 w,
-                style::Print(line),
+                style::SetBackgroundColor(if simulating {
+                    style::Color::DarkGreen
+                } else {
+                    style::Color::DarkRed
+                }),
+                style::SetForegroundColor(style::Color::White),
+                style::Print(format!(
+                    "Iteration: {}{} @ {}ms             ",
+                    n,
+                    if forward { "++" } else { "--" },
+                    dur.as_millis()
+                )),
                 cursor::MoveToNextLine(1),
                 style::ResetColor,
             )?;
+            for chars in lines {
+                let line: String = chars.into_iter().collect();
+                queue!(
+                    w,
+                    style::Print(line),
+                    cursor::MoveToNextLine(1),
+                    style::ResetColor,
+                )?;
+            }
+            queue!(
+                w,
+                style::Print(format!("Iteration: {}", n)),
+                cursor::MoveToNextLine(1),
+            )?;
+
+            w.flush()?;
         }
-        queue!(
-            w,
-            style::Print(format!("Iteration: {}", n)),
-            cursor::MoveToNextLine(1),
+
+        Ok("done".to_owned())
+    }
+
+    pub fn animate(input: &Input, bounds: Pos<isize>) -> Result<String> {
+        let mut bots = parse(input)?;
+
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(
+            stdout,
+            EnableBracketedPaste,
+            DisableLineWrap,
+            EnterAlternateScreen
         )?;
 
-        w.flush()?;
+        let r = part2_sim(&mut stdout, &mut bots, bounds);
+
+        execute!(
+            stdout,
+            DisableBracketedPaste,
+            EnableLineWrap,
+            LeaveAlternateScreen
+        )?;
+        disable_raw_mode()?;
+
+        r
+    }
+}
+
+pub struct Day14;
+impl Solver for Day14 {
+    fn day(&self) -> u8 {
+        14
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, bounds(params)?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        if params.get("animate")? {
+            #[cfg(feature = "tui")]
+            return animation::animate(input, bounds(params)?);
+            #[cfg(not(feature = "tui"))]
+            anyhow::bail!("animation needs the `tui` feature");
+        }
+        part2(input, bounds(params)?)
     }
 
-    Ok("done".to_owned())
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "width",
+                default: "101",
+                help: "width of the lobby",
+                check: Param::at_least::<1>,
+            },
+            Param {
+                key: "height",
+                default: "103",
+                help: "height of the lobby",
+                check: Param::at_least::<1>,
+            },
+            Param {
+                key: "animate",
+                default: "false",
+                help: "play part 2 in the terminal instead (needs the `tui` feature)",
+                check: Param::parses::<bool>,
+            },
+        ]
+    }
 }
 
-pub fn part2(lines: Vec<String>, bounds: Pos) -> Result<String> {
-    let mut bots = parse(lines)?;
-
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        EnableBracketedPaste,
-        DisableLineWrap,
-        EnterAlternateScreen
-    )?;
-
-    let r = part2_sim(&mut stdout, &mut bots, bounds);
-
-    execute!(
-        stdout,
-        DisableBracketedPaste,
-        EnableLineWrap,
-        LeaveAlternateScreen
-    )?;
-    disable_raw_mode()?;
-
-    r
+fn bounds(params: &Params) -> Result<Pos<isize>> {
+    Ok(Pos {
+        x: params.get("width")?,
+        y: params.get("height")?,
+    })
 }
 
 #[cfg(test)]
@@ -259,13 +358,9 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             p=0,4 v=3,-3
             p=6,3 v=-1,-3
             p=10,3 v=-1,2
@@ -279,7 +374,35 @@ mod test {
             p=2,4 v=2,-3
             p=9,5 v=-3,-3
         "});
-        assert_eq!(part1(lines, Pos::new(11, 7)?)?, "12");
+        assert_eq!(part1(&input, Pos { x: 11, y: 7 })?, "12");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        // Run a 5x5 block of bots backwards from the tree frame, so they only
+        // line up again at that frame
+        let bounds = Pos { x: 101, y: 103 };
+        let tree = 6377;
+        let text: Vec<String> = (0..25)
+            .map(|i: isize| {
+                let v = Pos {
+                    x: (i * 7) % 23 - 11,
+                    y: (i * 11) % 19 - 9,
+                };
+                let bot = Bot {
+                    p: Pos {
+                        x: 48 + i % 5,
+                        y: 50 + i / 5,
+                    },
+                    v,
+                };
+                let p = bot.at(-tree, bounds);
+                format!("p={},{} v={},{}", p.x, p.y, v.x, v.y)
+            })
+            .collect();
+        let input = Input::new(&text.join("\n"));
+        assert_eq!(part2(&input, bounds)?, tree.to_string());
         Ok(())
     }
 }
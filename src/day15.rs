@@ -1,9 +1,9 @@
-use std::fmt::{Display, Write};
-
 use anyhow::{anyhow, bail, Result};
+use log::{debug, trace};
 
-use crate::common::{Dir, Pos};
-type Point = Pos<isize>;
+use crate::common::{Dir, Grid, Input, Pos, Render};
+use crate::solver::{parsing, Params, Solver};
+type Point = Pos<usize>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Tile {
@@ -14,27 +14,42 @@ enum Tile {
     Bot,
     Wall,
 }
+impl Render for Tile {
+    fn render(&self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Bot => '@',
+            Tile::Box => 'O',
+            Tile::WideLeft => '[',
+            Tile::WideRight => ']',
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 struct Map {
     bot: Point,
-    bounds: Point,
-    tiles: Vec<Vec<Tile>>,
+    tiles: Grid<Tile>,
     moves: Vec<Dir>,
 }
 
 impl Map {
     fn get(&self, p: Point) -> Tile {
-        self.tiles[p.y as usize][p.x as usize]
+        self.tiles[p]
     }
 
     fn set(&mut self, p: Point, tile: Tile) {
-        self.tiles[p.y as usize][p.x as usize] = tile;
+        self.tiles[p] = tile;
     }
 
     fn peek_move(&self, p: Point, dir: Dir) -> bool {
-        let p_new = p.go(dir);
         let tile = self.get(p);
+        // Nothing can be pushed off an open edge of the map, as if it were
+        // a wall
+        let Some(p_new) = self.tiles.go(p, dir) else {
+            return tile == Tile::Empty;
+        };
         match tile {
             Tile::Wall => false,
             Tile::Empty => true,
@@ -63,8 +78,11 @@ impl Map {
     }
 
     fn do_move(&mut self, p: Point, dir: Dir) {
-        let p_new = p.go(dir);
         let tile = self.get(p);
+        // `peek_move` has already refused anything going off the edge
+        let Some(p_new) = self.tiles.go(p, dir) else {
+            return;
+        };
         match tile {
             Tile::Wall => {}
             Tile::Empty => {}
@@ -119,143 +137,121 @@ impl Map {
     }
 }
 
-impl Display for Map {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str("[\n")?;
-        for row in self.tiles.iter() {
-            for tile in row.iter() {
-                let c = match tile {
-                    Tile::Wall => '#',
-                    Tile::Empty => '.',
-                    Tile::Bot => '@',
-                    Tile::Box => 'O',
-                    Tile::WideLeft => '[',
-                    Tile::WideRight => ']',
-                };
-                f.write_char(c)?;
-            }
-            f.write_str("\n")?;
-        }
-        f.write_str("]")?;
-        Ok(())
+/// Doubles the width of a map row for part 2
+fn embiggen(line: &str) -> Result<String> {
+    let mut wide = String::with_capacity(line.len() * 2);
+    for c in line.chars() {
+        wide.push_str(match c {
+            '#' => "##",
+            'O' => "[]",
+            '.' => "..",
+            '@' => "@.",
+            _ => bail!("invalid tile {}", c),
+        });
     }
+    Ok(wide)
 }
 
-fn parse(lines: Vec<String>, two: bool) -> Result<Map> {
-    let mut iter = lines.into_iter().enumerate();
-    let (mut y, mut line_str) = iter.next().ok_or_else(|| anyhow!("missing line"))?;
-    let mut tiles: Vec<Vec<Tile>> = Vec::new();
-    let mut moves = Vec::new();
-    let mut bot = Point::default();
-    loop {
-        let mut row = Vec::new();
-        for (x, c) in line_str.chars().enumerate() {
-            match c {
-                '#' => row.push(Tile::Wall),
-                'O' => row.push(Tile::Box),
-                '.' => row.push(Tile::Empty),
-                '@' => {
-                    row.push(Tile::Bot);
-                    bot = (x, y).try_into()?;
-                }
-                _ => bail!("invalid tile {}", c),
-            }
-            if two {
-                // embiggen:
-                match c {
-                    '#' => row.push(Tile::Wall),
-                    'O' => {
-                        _ = row.pop(); // was Box
-                        row.push(Tile::WideLeft);
-                        row.push(Tile::WideRight);
-                    }
-                    '.' => row.push(Tile::Empty),
-                    '@' => {
-                        row.push(Tile::Empty);
-                        bot = (2 * x, y).try_into()?; // fix x coord
-                    }
-                    _ => bail!("invalid tile {}", c),
-                }
-            }
-        }
-        tiles.push(row);
+/// A tile of the map as the puzzle gives it
+fn tile(c: char) -> Result<Tile, String> {
+    Ok(match c {
+        '#' => Tile::Wall,
+        'O' => Tile::Box,
+        '.' => Tile::Empty,
+        '@' => Tile::Bot,
+        _ => return Err(format!("invalid tile {:?}", c)),
+    })
+}
 
-        (y, line_str) = iter.next().ok_or_else(|| anyhow!("missing line"))?;
-        if line_str == "" {
-            break;
-        }
+/// A tile of the map once `embiggen` has built it, the only place wide
+/// boxes come from
+fn wide_tile(c: char) -> Result<Tile, String> {
+    match c {
+        '[' => Ok(Tile::WideLeft),
+        ']' => Ok(Tile::WideRight),
+        _ => tile(c),
     }
+}
 
-    while let Some((_, line_str)) = iter.next() {
-        for c in line_str.chars() {
-            match c {
-                '<' => moves.push(Dir::Left),
-                '>' => moves.push(Dir::Right),
-                '^' => moves.push(Dir::Up),
-                'v' => moves.push(Dir::Down),
-                _ => bail!("invalid move {}", c),
-            }
+fn parse(input: &Input, two: bool) -> Result<Map> {
+    let [map, moves_section] = input.expect_sections(["map", "moves"])?;
+    let mut tiles = map.grid_with(tile)?;
+    if two {
+        // Already checked, so the wide map only needs building
+        let rows: Vec<String> = map.lines().map(embiggen).collect::<Result<_>>()?;
+        tiles = Grid::from_lines(rows, |c| wide_tile(c).map_err(|e| anyhow!(e)))?;
+    }
+    let bot = tiles.find('@').ok_or_else(|| anyhow!("missing bot"))?;
+
+    let mut moves = Vec::new();
+    for line in moves_section.numbered_lines() {
+        for (x, c) in line.text.chars().enumerate() {
+            let dir = Dir::from_char(c)
+                .ok_or_else(|| line.error_at(x, format!("invalid move {:?}", c)))?;
+            moves.push(dir);
         }
     }
 
-    let bounds = Point {
-        x: tiles.first().unwrap().len() as isize,
-        y: tiles.len() as isize,
-    };
-    Ok(Map {
-        bot,
-        bounds,
-        tiles,
-        moves,
-    })
+    Ok(Map { bot, tiles, moves })
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut map = parse(lines, false)?;
-    println!("bot: {:?}, bound: {:?}", map.bot, map.bounds);
-    println!("map: {}", map);
+pub fn part1(input: &Input) -> Result<String> {
+    let mut map = parsing(|| parse(input, false))?;
+    debug!("bot: {:?}, bound: {:?}", map.bot, map.tiles.bounds());
+    debug!("map:\n{}", map.tiles);
     for dir in map.moves.clone() {
         let p = map.bot;
         if map.peek_move(p, dir) {
             map.do_move(p, dir);
         }
     }
-    println!("after: {}", map);
+    debug!("after:\n{}", map.tiles);
 
-    let mut total: usize = 0;
-    for (y, row) in map.tiles.into_iter().enumerate() {
-        for (x, tile) in row.into_iter().enumerate() {
-            if tile == Tile::Box {
-                total += 100 * y + x;
-            }
-        }
-    }
+    let total: usize = map
+        .tiles
+        .iter()
+        .filter(|(_, tile)| **tile == Tile::Box)
+        .map(|(p, _)| 100 * p.y + p.x)
+        .sum();
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut map = parse(lines, true)?;
-    println!("bot: {:?}, bound: {:?}", map.bot, map.bounds);
-    println!("map: {}", map);
+pub fn part2(input: &Input) -> Result<String> {
+    let mut map = parsing(|| parse(input, true))?;
+    debug!("bot: {:?}, bound: {:?}", map.bot, map.tiles.bounds());
+    debug!("map:\n{}", map.tiles);
     for dir in map.moves.clone() {
         let p = map.bot;
         if map.peek_move(p, dir) {
             map.do_move(p, dir);
         }
-        // println!("after {:?}: {}", dir, map);
-    }
-    println!("after: {}", map);
-    let mut total: usize = 0;
-    for (y, row) in map.tiles.into_iter().enumerate() {
-        for (x, tile) in row.into_iter().enumerate() {
-            if tile == Tile::WideLeft {
-                total += 100 * y + x;
-            }
-        }
+        trace!("after {:?}:\n{}", dir, map.tiles);
     }
+    debug!("after:\n{}", map.tiles);
+    let total: usize = map
+        .tiles
+        .iter()
+        .filter(|(_, tile)| **tile == Tile::WideLeft)
+        .map(|(p, _)| 100 * p.y + p.x)
+        .sum();
     Ok(total.to_string())
 }
 
+pub struct Day15;
+impl Solver for Day15 {
+    fn day(&self) -> u8 {
+        15
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -263,13 +259,9 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ########
             #..O.O.#
             ##@.O..#
@@ -281,13 +273,39 @@ mod test {
 
             <^^>>>vv<v>>v<<
         "});
-        assert_eq!(part1(lines)?, "2028");
+        assert_eq!(part1(&input)?, "2028");
         Ok(())
     }
 
+    #[test]
+    fn test_open_edge() -> Result<()> {
+        let input = Input::new(indoc! {"
+            #.@O.
+
+            >>>
+        "});
+        assert_eq!(part1(&input)?, "4");
+        assert_eq!(part2(&input)?, "8");
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_tiles_rejected() {
+        let input = Input::new(".\n]\n@\n\n^");
+        for e in [part1(&input), part2(&input)] {
+            assert_eq!(
+                e.unwrap_err().to_string(),
+                indoc! {"
+                    line 2, column 1: invalid tile ']'
+                       2 | ]
+                         | ^"}
+            );
+        }
+    }
+
     #[test]
     fn test_part1_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ##########
             #..O..O.O#
             #......O.#
@@ -310,13 +328,13 @@ mod test {
             ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
             v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
         "});
-        assert_eq!(part1(lines)?, "10092");
+        assert_eq!(part1(&input)?, "10092");
         Ok(())
     }
 
     #[test]
     fn test_part2_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #######
             #...#.#
             #.....#
@@ -327,13 +345,13 @@ mod test {
 
             <vv<<^^<<^^
         "});
-        assert_eq!(part2(lines)?, (105 + 207 + 306).to_string());
+        assert_eq!(part2(&input)?, (105 + 207 + 306).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part2_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ##########
             #..O..O.O#
             #......O.#
@@ -356,7 +374,7 @@ mod test {
             ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
             v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
         "});
-        assert_eq!(part2(lines)?, "9021");
+        assert_eq!(part2(&input)?, "9021");
         Ok(())
     }
 }
@@ -1,190 +1,156 @@
 use std::collections::HashSet;
 
-use anyhow::{bail, Result};
-use graphrs::{algorithms::shortest_path::dijkstra, Graph, GraphSpecs};
+use anyhow::{anyhow, Result};
 
-use crate::common::{graphrs_anyhow, Dir, Pos};
+use crate::common::search::{dijkstra, Paths};
+use crate::common::{Dir, Grid, Input, Pos, Render};
+use crate::solver::{parsing, Param, Params, Solver};
 type Point = Pos<usize>;
 
+/// A reindeer's position and heading
+type State = (Point, Dir);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Tile {
+    Wall,
+    Empty,
+    Start,
+    End,
+}
+impl Render for Tile {
+    fn render(&self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Start => 'S',
+            Tile::End => 'E',
+        }
+    }
+}
+
+/// Score for each step forward and each 90° turn
+#[derive(Debug, Copy, Clone)]
+pub struct Costs {
+    pub step: usize,
+    pub turn: usize,
+}
+
 #[derive(Debug, Clone)]
 struct Map {
-    spaces: HashSet<Point>,
+    tiles: Grid<Tile>,
     start: Point,
     end: Point,
 }
 
 impl Map {
     fn has(&self, p: &Point) -> bool {
-        self.spaces.contains(&p)
+        self.tiles[*p] != Tile::Wall
     }
-}
 
-fn parse(lines: Vec<String>) -> Result<Map> {
-    let mut spaces = HashSet::new();
-    let mut start = Point::default();
-    let mut end = Point::default();
-    for (y, line_str) in lines.into_iter().enumerate() {
-        for (x, c) in line_str.chars().enumerate() {
-            match c {
-                '#' => {}
-                '.' => {
-                    spaces.insert(Point { x, y });
-                }
-                'S' => start = Point { x, y },
-                'E' => end = Point { x, y },
-                _ => bail!("invalid tile {}", c),
+    /// Step forward if there's room, or turn either way on the spot
+    fn moves(&self, (p, dir): &State, costs: Costs) -> Vec<(State, usize)> {
+        let mut out = vec![((*p, dir.ccw()), costs.turn), ((*p, dir.cw()), costs.turn)];
+        if let Some(ahead) = self.tiles.go(*p, *dir) {
+            if self.has(&ahead) {
+                out.push(((ahead, *dir), costs.step));
             }
         }
+        out
     }
-
-    Ok(Map { spaces, start, end })
 }
 
-fn map_to_graph(map: &Map) -> Result<Graph<Point, ()>> {
-    let mut q: Vec<(Point, Point, f64)> = Vec::new();
-    //let e = Edge::with_weight(u, v, weight)
-    let mut g = Graph::new(GraphSpecs::directed_create_missing());
-    for space in map.spaces.iter() {
-        let u = space.go(Dir::Up);
-        let r = space.go(Dir::Right);
-        let d = space.go(Dir::Down);
-        let l = space.go(Dir::Left);
-        let has_u = map.has(&u);
-        let has_r = map.has(&r);
-        let has_d = map.has(&d);
-        let has_l = map.has(&l);
-        // skips: forward, forward == 2
-        if has_u && has_d {
-            q.push((u, d, 2.));
-            q.push((d, u, 2.));
-        }
-        if has_l && has_r {
-            q.push((l, r, 2.));
-            q.push((r, l, 2.));
-        }
-        // corners: forward, turn, forward == 92
-        if has_u && has_r {
-            q.push((u, r, 1002.));
-            q.push((r, u, 1002.));
-        }
-        if has_r && has_d {
-            q.push((d, r, 1002.));
-            q.push((r, d, 1002.));
-        }
-        if has_l && has_d {
-            q.push((d, l, 1002.));
-            q.push((l, d, 1002.));
-        }
-        if has_l && has_u {
-            q.push((u, l, 1002.));
-            q.push((l, u, 1002.));
-        }
-    }
+fn parse(input: &Input) -> Result<Map> {
+    let tiles = input.grid_with(|c| {
+        Ok(match c {
+            '#' => Tile::Wall,
+            '.' => Tile::Empty,
+            'S' => Tile::Start,
+            'E' => Tile::End,
+            _ => return Err(format!("invalid tile {:?}", c)),
+        })
+    })?;
+    let start = tiles.find('S').ok_or_else(|| anyhow!("missing start"))?;
+    let end = tiles.find('E').ok_or_else(|| anyhow!("missing end"))?;
+    Ok(Map { tiles, start, end })
+}
 
-    {
-        // From start assuming it's in bottom left hallway'd corner
-        let s_u = map.start.go(Dir::Up);
-        let s_r = map.start.go(Dir::Right);
-        if map.has(&s_u) {
-            q.push((map.start, s_u, 1001.)); // turn left, fwd
-        }
-        if map.has(&s_r) {
-            q.push((map.start, s_r, 1.)); // fwd
-        }
-    }
+/// Searches from the start facing east. Returns the paths found, the lowest
+/// score at the end and the end states (headings) that reach it.
+fn search(map: &Map, costs: Costs) -> Result<(Paths<State>, usize, Vec<State>)> {
+    let paths = dijkstra((map.start, Dir::Right), |u| map.moves(u, costs));
+    let ends: Vec<(State, usize)> = enum_iterator::all::<Dir>()
+        .filter_map(|dir| {
+            let end = (map.end, dir);
+            Some((end, paths.cost(&end)?))
+        })
+        .collect();
+    let best = ends
+        .iter()
+        .map(|(_, cost)| *cost)
+        .min()
+        .ok_or_else(|| anyhow!("no path to the end"))?;
+    let ends = ends
+        .into_iter()
+        .filter(|(_, cost)| *cost == best)
+        .map(|(end, _)| end)
+        .collect();
+    Ok((paths, best, ends))
+}
 
-    {
-        // To end, assuming it's always in a hallway'd corner in top right
-        // (examples and personal challenge seems that way)
-        let e_d = map.end.go(Dir::Down);
-        if map.has(&e_d) {
-            q.push((e_d, map.end, 1.));
-        }
-        let e_l = map.end.go(Dir::Left);
-        if map.has(&e_l) {
-            q.push((e_l, map.end, 1.));
-        }
-    }
+pub fn part1(input: &Input, costs: Costs) -> Result<String> {
+    let map = parsing(|| parse(input))?;
+    let (_, best, _) = search(&map, costs)?;
+    Ok(best.to_string())
+}
 
-    g.add_edge_tuples_weighted(q).map_err(graphrs_anyhow)?;
+pub fn part2(input: &Input, costs: Costs) -> Result<String> {
+    let map = parsing(|| parse(input))?;
+    let (paths, _, ends) = search(&map, costs)?;
+    let tiles: HashSet<Point> = paths
+        .on_paths_to(ends)
+        .into_iter()
+        .map(|(p, _)| p)
+        .collect();
+    Ok(tiles.len().to_string())
+}
 
-    Ok(g)
+fn costs(params: &Params) -> Result<Costs> {
+    Ok(Costs {
+        step: params.get("step")?,
+        turn: params.get("turn")?,
+    })
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
-    let g = map_to_graph(&map)?;
-    let sp = dijkstra::single_source(&g, true, map.start, Some(map.end), None, true, true)
-        .map_err(graphrs_anyhow)?;
-    let mut total: usize = 0;
-    if let Some(info) = sp.get(&map.end) {
-        println!("from {} = {}", map.start, info.distance);
-        println!("{:?}", info.paths.first());
-        total = info.distance.round() as usize;
+pub struct Day16;
+impl Solver for Day16 {
+    fn day(&self) -> u8 {
+        16
     }
-    Ok(total.to_string())
-}
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let map = parse(lines)?;
-    let g = map_to_graph(&map)?;
-    let sp = dijkstra::single_source(&g, true, map.start, Some(map.end), None, false, true)
-        .map_err(graphrs_anyhow)?;
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, costs(params)?)
+    }
 
-    let mut travelled: HashSet<Point> = HashSet::new();
-    if let Some(info) = sp.get(&map.end) {
-        println!(
-            "from {} = {}, {} paths",
-            map.start,
-            info.distance,
-            info.paths.len()
-        );
-        for path in info.paths.iter() {
-            let mut iter = path.iter();
-            let mut prev = iter.next().unwrap();
-            travelled.insert(*prev);
-            while let Some(next) = iter.next() {
-                travelled.insert(*next);
-                let dx = prev.x.abs_diff(next.x);
-                let dy = prev.y.abs_diff(next.y);
-                if (dx == 0 && dy == 1) || (dx == 1 && dy == 0) {
-                    // already done
-                } else if dx == 2 {
-                    // skip horiz
-                    let missing_x = (prev.x + next.x) / 2;
-                    travelled.insert(Point {
-                        x: missing_x,
-                        y: prev.y,
-                    });
-                } else if dy == 2 {
-                    // skip vert
-                    let missing_y = (prev.y + next.y) / 2;
-                    travelled.insert(Point {
-                        x: prev.x,
-                        y: missing_y,
-                    });
-                } else if dx == 1 && dy == 1 {
-                    // corner
-                    let cand1 = Point {
-                        x: next.x,
-                        y: prev.y,
-                    };
-                    let cand2 = Point {
-                        x: prev.x,
-                        y: next.y,
-                    };
-                    if map.has(&cand1) {
-                        travelled.insert(cand1);
-                    } else {
-                        travelled.insert(cand2);
-                    }
-                }
-                prev = next;
-            }
-        }
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, costs(params)?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "step",
+                default: "1",
+                help: "score for a step forward",
+                check: Param::parses::<usize>,
+            },
+            Param {
+                key: "turn",
+                default: "1000",
+                help: "score for a 90 degree turn",
+                check: Param::parses::<usize>,
+            },
+        ]
     }
-    let total: usize = travelled.len();
-    Ok(total.to_string())
 }
 
 #[cfg(test)]
@@ -194,13 +160,14 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
+    const REINDEER: Costs = Costs {
+        step: 1,
+        turn: 1000,
+    };
 
     #[test]
     fn test_part1_ez() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #######
             #...#E#
             #.#.#.#
@@ -208,7 +175,7 @@ mod test {
             #######
         "});
         assert_eq!(
-            part1(lines)?,
+            part1(&input, REINDEER)?,
             (1001 + 1002 + 1002 + 1002 + 1002 + 1).to_string()
         );
         Ok(())
@@ -216,7 +183,7 @@ mod test {
 
     #[test]
     fn test_part1_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ###############
             #.......#....E#
             #.#.###.#.###.#
@@ -233,13 +200,13 @@ mod test {
             #S..#.....#...#
             ###############
         "});
-        assert_eq!(part1(lines)?, "7036");
+        assert_eq!(part1(&input, REINDEER)?, "7036");
         Ok(())
     }
 
     #[test]
     fn test_part1_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #################
             #...#...#...#..E#
             #.#.#.#.#.#.#.#.#
@@ -258,13 +225,13 @@ mod test {
             #S#.............#
             #################
         "});
-        assert_eq!(part1(lines)?, "11048");
+        assert_eq!(part1(&input, REINDEER)?, "11048");
         Ok(())
     }
 
     #[test]
     fn test_part2_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ###############
             #.......#....E#
             #.#.###.#.###.#
@@ -281,13 +248,13 @@ mod test {
             #S..#.....#...#
             ###############
         "});
-        assert_eq!(part2(lines)?, "45");
+        assert_eq!(part2(&input, REINDEER)?, "45");
         Ok(())
     }
 
     #[test]
     fn test_part2_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #################
             #...#...#...#..E#
             #.#.#.#.#.#.#.#.#
@@ -306,7 +273,34 @@ mod test {
             #S#.............#
             #################
         "});
-        assert_eq!(part2(lines)?, "64");
+        assert_eq!(part2(&input, REINDEER)?, "64");
+        Ok(())
+    }
+
+    fn corners() -> Input {
+        Input::new(indoc! {"
+            #####
+            #E..#
+            #.#.#
+            #..S#
+            #####
+        "})
+    }
+
+    #[test]
+    fn test_start_end_anywhere() -> Result<()> {
+        // Blocked facing east, so turn north and go round
+        assert_eq!(part1(&corners(), REINDEER)?, "2004");
+        assert_eq!(part2(&corners(), REINDEER)?, "5");
+        Ok(())
+    }
+
+    #[test]
+    fn test_free_turns() -> Result<()> {
+        let costs = Costs { step: 1, turn: 0 };
+        assert_eq!(part1(&corners(), costs)?, "4");
+        // Both ways round are equally good
+        assert_eq!(part2(&corners(), costs)?, "8");
         Ok(())
     }
 }
@@ -1,5 +1,12 @@
-use anyhow::{bail, Result};
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Result};
 use itertools::join;
+use log::debug;
+
+use crate::common::Input;
+use crate::solver::{parsing, Param, Params, Solver};
 
 #[derive(Clone, Debug)]
 struct Computer {
@@ -10,6 +17,86 @@ struct Computer {
     mem: Vec<u8>,
     out: Vec<u8>,
 }
+
+const MNEMONICS: [&str; 8] = ["adv", "bxl", "bst", "jnz", "bxc", "out", "bdv", "cdv"];
+
+/// How a combo operand reads, e.g. `A` for 4
+fn combo_name(v: u8) -> String {
+    match v {
+        0..=3 => v.to_string(),
+        4 => "A".to_owned(),
+        5 => "B".to_owned(),
+        6 => "C".to_owned(),
+        _ => format!("<invalid combo {}>", v),
+    }
+}
+
+/// One instruction as mnemonic, decoded operand and what it does, e.g.
+/// `adv 3` and `A = A >> 3`
+fn describe(opcode: u8, operand: u8) -> (String, String) {
+    let x = combo_name(operand);
+    let (arg, effect) = match opcode {
+        0 => (x.clone(), format!("A = A >> {}", x)),
+        1 => (operand.to_string(), format!("B = B ^ {}", operand)),
+        2 => (x.clone(), format!("B = {} % 8", x)),
+        3 => (
+            operand.to_string(),
+            format!("jump to {} if A != 0", operand),
+        ),
+        4 => (String::new(), "B = B ^ C".to_owned()),
+        5 => (x.clone(), format!("output {} % 8", x)),
+        6 => (x.clone(), format!("B = A >> {}", x)),
+        7 => (x.clone(), format!("C = A >> {}", x)),
+        _ => return (format!("<invalid opcode {}>", opcode), String::new()),
+    };
+    let mnemonic = MNEMONICS[opcode as usize];
+    (
+        format!("{} {}", mnemonic, arg).trim_end().to_owned(),
+        effect,
+    )
+}
+
+/// Where `--trace` sends each executed instruction, always on stderr
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Trace {
+    Off,
+    Plain,
+    /// One JSON object per line
+    Json,
+}
+
+impl FromStr for Trace {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "off" | "false" => Ok(Self::Off),
+            "plain" | "true" => Ok(Self::Plain),
+            "json" => Ok(Self::Json),
+            _ => Err(anyhow!("expected off, plain or json")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Registers {
+    a: usize,
+    b: usize,
+    c: usize,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A={} B={} C={}", self.a, self.b, self.c)
+    }
+}
+
+impl Registers {
+    fn json(&self) -> String {
+        format!(r#"{{"a":{},"b":{},"c":{}}}"#, self.a, self.b, self.c)
+    }
+}
+
 impl Computer {
     fn read(&mut self) -> Result<usize> {
         if let Some(v) = self.mem.get(self.ip) {
@@ -23,7 +110,7 @@ impl Computer {
     fn combo(&mut self) -> Result<usize> {
         let v = self.read()?;
         Ok(match v {
-            0 | 1 | 2 | 3 => v as usize,
+            0..=3 => v,
             4 => self.a,
             5 => self.b,
             6 => self.c,
@@ -36,6 +123,27 @@ impl Computer {
         Ok(self.a >> v)
     }
 
+    fn registers(&self) -> Registers {
+        Registers {
+            a: self.a,
+            b: self.b,
+            c: self.c,
+        }
+    }
+
+    /// The program as one instruction per line, with combo operands decoded
+    fn disassemble(&self) -> String {
+        let mut out = String::new();
+        for (ip, chunk) in self.mem.chunks(2).enumerate() {
+            let (instr, effect) = match *chunk {
+                [opcode, operand] => describe(opcode, operand),
+                _ => (format!("<missing operand for {}>", chunk[0]), String::new()),
+            };
+            out += &format!("{:>2}: {:<8} ; {}\n", ip * 2, instr, effect);
+        }
+        out
+    }
+
     fn simulate(&mut self, two: bool) -> Result<()> {
         if self.ip >= self.mem.len() {
             bail!("halt");
@@ -46,39 +154,34 @@ impl Computer {
             0 => {
                 // adv (really "right shift")
                 self.a = self.shr()?;
-                // println!("adv {:?}", self);
             }
             1 => {
                 // bxl
                 let x = self.b;
                 let y = self.read()?;
                 self.b = x ^ y;
-                // println!("bxl {:?}", self);
             }
             2 => {
                 // bst
                 self.b = self.combo()? % 8;
-                // println!("bst {:?}", self);
             }
             3 => {
-                // jnz
+                // jnz. The operand is read even when the jump isn't
+                // taken, so execution carries on after it
+                let ip = self.read()?;
                 if self.a != 0 {
-                    let ip = self.read()?;
                     self.ip = ip;
                 }
-                // println!("jnz {:?}", self);
             }
             4 => {
                 // bxc
-                self.b = self.b ^ self.c;
+                self.b ^= self.c;
                 _ = self.read()?; // "legacy reasons"
-                                  // println!("bxc {:?}", self);
             }
             5 => {
                 // out
                 let v = self.combo()?;
                 self.out.push((v % 8).try_into()?);
-                // println!("out {:?}", self);
                 if two && !self.mem.starts_with(&self.out) {
                     bail!("early")
                 }
@@ -86,29 +189,79 @@ impl Computer {
             6 => {
                 // bdv
                 self.b = self.shr()?;
-                // println!("bdv {:?}", self);
             }
             7 => {
                 // cdv
                 self.c = self.shr()?;
-                // println!("cdv {:?}", self);
             }
             _ => {}
         }
         Ok(())
     }
+
+    /// `simulate`, logging the instruction and registers around it
+    fn step(&mut self, two: bool, trace: Trace) -> Result<()> {
+        if trace == Trace::Off || self.ip >= self.mem.len() {
+            return self.simulate(two);
+        }
+        let ip = self.ip;
+        let before = self.registers();
+        let outputs = self.out.len();
+        let opcode = self.mem[ip];
+        let operand = self.mem.get(ip + 1).copied().unwrap_or(0);
+        let r = self.simulate(two);
+        let after = self.registers();
+        let out = self.out.get(outputs);
+        match trace {
+            Trace::Off => {}
+            Trace::Plain => eprintln!(
+                "{:>2}: {:<8} {} -> {}{}",
+                ip,
+                describe(opcode, operand).0,
+                before,
+                after,
+                out.map_or(String::new(), |v| format!(" out {}", v))
+            ),
+            Trace::Json => eprintln!(
+                r#"{{"ip":{},"opcode":{},"operand":{},"instruction":"{}","before":{},"after":{},"out":{}}}"#,
+                ip,
+                opcode,
+                operand,
+                describe(opcode, operand).0,
+                before.json(),
+                after.json(),
+                out.map_or("null".to_owned(), |v| v.to_string())
+            ),
+        }
+        r
+    }
 }
 
-fn parse(lines: Vec<String>) -> Result<Computer> {
-    let mut iter = lines.into_iter();
-    let a = iter.next().unwrap().split_once(": ").unwrap().1.parse()?;
-    let b = iter.next().unwrap().split_once(": ").unwrap().1.parse()?;
-    let c = iter.next().unwrap().split_once(": ").unwrap().1.parse()?;
-    iter.next();
-    let mut mem = Vec::new();
-    for digit in iter.next().unwrap().split_once(": ").unwrap().1.split(',') {
-        mem.push(digit.parse()?);
+fn parse(input: &Input) -> Result<Computer> {
+    let [registers, program] = input.expect_sections(["registers", "program"])?;
+    let mut regs = Vec::new();
+    for (line, name) in registers.numbered_lines().zip(["A", "B", "C"]) {
+        let (reg, value) = line.split_once(": ", &format!("`Register {}: N`", name))?;
+        if reg != format!("Register {}", name) {
+            return Err(line
+                .error_in(reg, format!("expected register {}", name))
+                .into());
+        }
+        regs.push(line.parse(value)?);
     }
+    let [a, b, c] = regs[..] else {
+        return Err(registers
+            .error_at_end("expected registers A, B and C")
+            .into());
+    };
+    let line = program.line()?;
+    let mem = match line.text.strip_prefix("Program: ") {
+        Some(list) => list
+            .split(',')
+            .map(|n| line.parse(n))
+            .collect::<Result<_, _>>()?,
+        None => return Err(line.error("expected `Program: N,N,...`").into()),
+    };
 
     Ok(Computer {
         a,
@@ -120,83 +273,136 @@ fn parse(lines: Vec<String>) -> Result<Computer> {
     })
 }
 
-fn part1_sim(c: &mut Computer) -> Result<String> {
+fn part1_sim(c: &mut Computer, trace: Trace) -> Result<String> {
+    if trace == Trace::Plain {
+        eprint!("{}", c.disassemble());
+    }
     loop {
-        if let Err(e) = c.simulate(false) {
-            println!("{:?}", e);
+        if let Err(e) = c.step(false, trace) {
+            debug!("stopped: {}", e);
             break;
         }
     }
     Ok(join(c.out.iter().map(|c| c.to_string()), ","))
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut c = parse(lines)?;
-    part1_sim(&mut c)
+fn part1(input: &Input, trace: Trace) -> Result<String> {
+    let mut c = parsing(|| parse(input))?;
+    part1_sim(&mut c, trace)
 }
 
-fn sim_one_loop_hardcoded(a: usize) -> u8 {
-    // bst 4(a) - "take lower bits of a"
-    let mut b = a % 8;
-
-    // bxl b, 3
-    b = b ^ 3;
-
-    // cdv 5(b)
-    let c = a >> b;
-
-    // adv 3 - "shift off lower bits of a"
-    //a = a >> 3;
-
-    // bxc _
-    b = b ^ c;
-
-    // bxl b, 5
-    b = b ^ 5;
+impl Computer {
+    /// Checks the program is one loop ending in the only `jnz 0`, which is
+    /// what part 2 relies on
+    fn check_loop_shape(&self) -> Result<()> {
+        let n = self.mem.len();
+        if !n.is_multiple_of(2) || !self.mem.ends_with(&[3, 0]) {
+            bail!("program must end with `jnz 0` looping back to the start");
+        }
+        if self.mem[..n - 2].iter().step_by(2).any(|&op| op == 3) {
+            bail!("program must not jump anywhere except from its last instruction");
+        }
+        Ok(())
+    }
 
-    // out 5(b)
-    (b % 8) as u8
-    // then it jnz's back to start
+    /// Runs the loop body once with `a` in register A, up to the final
+    /// `jnz`. Returns the single output and the value left in A.
+    fn run_loop(&self, a: usize) -> Result<(u8, usize)> {
+        let mut c = self.clone();
+        c.a = a;
+        c.ip = 0;
+        c.out.clear();
+        while c.ip < c.mem.len() - 2 {
+            c.simulate(false)?;
+        }
+        if c.out.len() != 1 {
+            bail!(
+                "program must output exactly one value per loop, got {} for A={}",
+                c.out.len(),
+                a
+            );
+        }
+        if c.a != a >> 3 {
+            bail!(
+                "program must shift A right by 3 each loop, got {} for A={}",
+                c.a,
+                a
+            );
+        }
+        Ok((c.out[0], c.a))
+    }
 }
 
-fn part2_sim(orig_computer: &Computer, a: usize) -> bool {
+fn part2_sim(orig_computer: &Computer, a: usize, trace: Trace) -> bool {
     let mut c2 = orig_computer.clone();
     c2.a = a;
     loop {
-        if c2.simulate(true).is_err() {
+        if c2.step(true, trace).is_err() {
             break;
         }
     }
-    return c2.out == orig_computer.mem;
+    c2.out == orig_computer.mem
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let c = parse(lines)?;
-    let mut desired = c.mem.clone();
-    desired.reverse();
+/// With `trace`, logs the final run that checks the answer
+fn part2(input: &Input, trace: Trace) -> Result<String> {
+    let c = parsing(|| parse(input))?;
+    if trace == Trace::Plain {
+        eprint!("{}", c.disassemble());
+    }
+    c.check_loop_shape()?;
+
+    // Each loop consumes the low 3 bits of A, so build A from the last output
+    // backwards, trying the 8 possible bits below what's been found so far.
+    // A must stay non-zero until the final loop or the program halts early.
     let mut candidates: Vec<usize> = vec![0];
-    for d in desired {
+    for &d in c.mem.iter().rev() {
         let mut found = vec![];
         for candidate in candidates {
             for lower_bits in 0..8 {
                 let a = (candidate << 3) + lower_bits;
-                if sim_one_loop_hardcoded(a) == d {
+                if a != 0 && c.run_loop(a)?.0 == d {
                     found.push(a);
                 }
             }
         }
         candidates = found;
     }
-    candidates.sort();
-    let lowest = candidates.first().unwrap();
+    let Some(lowest) = candidates.into_iter().min() else {
+        bail!("no value of A makes the program output itself");
+    };
 
-    if !part2_sim(&c, *lowest) {
-        bail!("wtf")
+    if !part2_sim(&c, lowest, trace) {
+        bail!("A={} doesn't reproduce the program", lowest);
     }
 
     Ok(lowest.to_string())
 }
 
+pub struct Day17;
+impl Solver for Day17 {
+    fn day(&self) -> u8 {
+        17
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[Param {
+            key: "trace",
+            default: "off",
+            help: "log each instruction to stderr: off, plain or json",
+            check: Param::parses::<Trace>,
+        }]
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, params.get("trace")?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, params.get("trace")?)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -204,10 +410,6 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1_a() -> Result<()> {
         let mut c = Computer {
@@ -218,11 +420,27 @@ mod test {
             mem: vec![2, 6],
             out: Vec::new(),
         };
-        assert_eq!(part1_sim(&mut c)?, "");
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "");
         assert_eq!(c.b, 1);
         Ok(())
     }
 
+    #[test]
+    fn test_jnz_not_taken_skips_operand() -> Result<()> {
+        // With A = 0 the jump operand is still consumed, so `out 5` runs
+        // next rather than the operand being decoded as `bxl`
+        let mut c = Computer {
+            a: 0,
+            b: 3,
+            c: 0,
+            ip: 0,
+            mem: vec![3, 1, 5, 5],
+            out: Vec::new(),
+        };
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "3");
+        Ok(())
+    }
+
     #[test]
     fn test_part1_b() -> Result<()> {
         let mut c = Computer {
@@ -233,7 +451,7 @@ mod test {
             mem: vec![5, 0, 5, 1, 5, 4],
             out: Vec::new(),
         };
-        assert_eq!(part1_sim(&mut c)?, "0,1,2");
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "0,1,2");
         Ok(())
     }
 
@@ -247,7 +465,7 @@ mod test {
             mem: vec![0, 1, 5, 4, 3, 0],
             out: Vec::new(),
         };
-        assert_eq!(part1_sim(&mut c)?, "4,2,5,6,7,7,7,7,3,1,0");
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "4,2,5,6,7,7,7,7,3,1,0");
         assert_eq!(c.a, 0);
         Ok(())
     }
@@ -262,7 +480,7 @@ mod test {
             mem: vec![1, 7],
             out: Vec::new(),
         };
-        assert_eq!(part1_sim(&mut c)?, "");
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "");
         assert_eq!(c.b, 26);
         Ok(())
     }
@@ -277,41 +495,95 @@ mod test {
             mem: vec![4, 0],
             out: Vec::new(),
         };
-        assert_eq!(part1_sim(&mut c)?, "");
+        assert_eq!(part1_sim(&mut c, Trace::Off)?, "");
         assert_eq!(c.b, 44354);
         Ok(())
     }
 
     #[test]
     fn test_part1_big() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             Register A: 729
             Register B: 0
             Register C: 0
 
             Program: 0,1,5,4,3,0
         "});
-        assert_eq!(part1(lines)?, "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(part1(&input, Trace::Off)?, "4,6,3,5,6,3,5,2,1,0");
         Ok(())
     }
 
-    // Didn't write a generic solution, so this example doesn't work:
-    // But this one is super-easy to reverse engineer:
-    //  shift A right 3 bits
-    //  output lower 3 bits of A
-    //  loop if A != 0
-    // #[test]
-    // fn test_part2() -> Result<()> {
-    //     let lines = lines(indoc! {"
-    //         Register A: 2024
-    //         Register B: 0
-    //         Register C: 0
-
-    //         Program: 0,3,5,4,3,0
-    //     "});
-    //     assert_eq!(part2(lines)?, "117440");
-    //     Ok(())
-    // }
+    #[test]
+    fn test_disassemble() {
+        let c = Computer {
+            a: 0,
+            b: 0,
+            c: 0,
+            ip: 0,
+            mem: vec![2, 4, 1, 3, 7, 5, 4, 1, 0, 3, 5, 5, 3, 0],
+            out: Vec::new(),
+        };
+        assert_eq!(
+            c.disassemble(),
+            indoc! {"
+                  0: bst A    ; B = A % 8
+                  2: bxl 3    ; B = B ^ 3
+                  4: cdv B    ; C = A >> B
+                  6: bxc      ; B = B ^ C
+                  8: adv 3    ; A = A >> 3
+                 10: out B    ; output B % 8
+                 12: jnz 0    ; jump to 0 if A != 0
+            "}
+        );
+    }
+
+    #[test]
+    fn test_part2() -> Result<()> {
+        let input = Input::new(indoc! {"
+            Register A: 2024
+            Register B: 0
+            Register C: 0
+
+            Program: 0,3,5,4,3,0
+        "});
+        assert_eq!(part2(&input, Trace::Off)?, "117440");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_input_shape() -> Result<()> {
+        let c = Computer {
+            a: 0,
+            b: 0,
+            c: 0,
+            ip: 0,
+            mem: vec![2, 4, 1, 3, 7, 5, 0, 3, 4, 1, 1, 5, 5, 5, 3, 0],
+            out: Vec::new(),
+        };
+        c.check_loop_shape()?;
+        assert_eq!(c.run_loop(236581108670061)?.1, 236581108670061 >> 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_unsupported() {
+        let program = |p: &str| {
+            Input::new(&format!(
+                "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+                p
+            ))
+        };
+        // No loop at all
+        assert!(part2(&program("0,3,5,4"), Trace::Off).is_err());
+        // Jumps from the middle
+        assert!(part2(&program("3,4,0,3,5,4,3,0"), Trace::Off).is_err());
+        // Shifts A by 1 per loop
+        let e = part2(&program("0,1,5,4,3,0"), Trace::Off).unwrap_err();
+        assert!(e.to_string().contains("shift A right by 3"), "{}", e);
+        // Outputs twice per loop
+        let e = part2(&program("0,3,5,4,5,4,3,0"), Trace::Off).unwrap_err();
+        assert!(e.to_string().contains("exactly one value"), "{}", e);
+    }
 
     #[test]
     fn test_part2_confirm() -> Result<()> {
@@ -324,8 +596,8 @@ mod test {
             mem: vec![2, 4, 1, 3, 7, 5, 0, 3, 4, 1, 1, 5, 5, 5, 3, 0],
             out: Vec::new(),
         };
-        let o1 = part2_sim(&c1, a1);
-        assert_eq!(o1, true);
+        let o1 = part2_sim(&c1, a1, Trace::Off);
+        assert!(o1);
         Ok(())
     }
 }
@@ -1,10 +1,12 @@
-use std::fmt::{Display, Formatter, Write};
+use std::collections::HashSet;
 
-use anyhow::{bail, Result};
-use graphrs::{algorithms::shortest_path::dijkstra, Edge, Graph, GraphSpecs};
+use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
+use log::{debug, trace};
 
-use crate::common::{graphrs_anyhow, Dir, Pos};
+use crate::common::search::astar_manhattan;
+use crate::common::{Grid, Input, Pos, Render};
+use crate::solver::{parsing, Param, Params, Solver};
 type Point = Pos<usize>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -14,103 +16,67 @@ enum Tile {
     Mark,
 }
 
+impl Render for Tile {
+    fn render(&self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Mark => 'O',
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Map {
-    bounds: Point,
-    tiles: Vec<Vec<Tile>>,
+    tiles: Grid<Tile>,
     seq: Vec<Point>,
 }
 
 impl Map {
     fn get(&self, p: Point) -> Tile {
-        self.tiles[p.y][p.x]
+        self.tiles[p]
     }
 
     fn set(&mut self, p: Point, tile: Tile) {
-        self.tiles[p.y][p.x] = tile;
+        self.tiles[p] = tile;
     }
 
-    fn go_bounded(&self, p: &Point, dir: Dir) -> Option<Point> {
-        p.go_bounded(dir, &self.bounds)
+    /// A shortest path from `start` to `end`, unless the bytes cut it off
+    fn shortest_path(&self, start: Point, end: Point) -> Option<Vec<Point>> {
+        let (_, path) = astar_manhattan(start, end, |u| {
+            self.tiles
+                .neighbours(*u)
+                .filter(|(_, v)| self.get(*v) == Tile::Empty)
+                .map(|(_, v)| (v, 1))
+        })?;
+        Some(path)
     }
 }
 
-impl Display for Map {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        f.write_str("[\n")?;
-        for row in self.tiles.iter() {
-            for tile in row.iter() {
-                let c = match tile {
-                    Tile::Wall => '#',
-                    Tile::Empty => '.',
-                    Tile::Mark => 'O',
-                };
-                f.write_char(c)?;
-            }
-            f.write_str("\n")?;
-        }
-        f.write_str("]")?;
-        Ok(())
-    }
-}
-
-fn parse(lines: Vec<String>, bounds: Point) -> Result<Map> {
-    let seq = lines
-        .into_iter()
+fn parse(input: &Input, bounds: Point) -> Result<Map> {
+    let seq = input
+        .numbered_lines()
         .map(|line| {
-            if let Some((x, y)) = line.split_once(',') {
-                Ok(Point {
-                    x: x.parse()?,
-                    y: y.parse()?,
-                })
-            } else {
-                bail!("can't split");
+            let (x, y) = line.split_once(",", "`X,Y`")?;
+            let p = Point {
+                x: line.parse(x)?,
+                y: line.parse(y)?,
+            };
+            if p.x >= bounds.x || p.y >= bounds.y {
+                return Err(line.error(format!(
+                    "outside the {}x{} memory space",
+                    bounds.x, bounds.y
+                )));
             }
+            Ok(p)
         })
         .try_collect()?;
-    let tiles = std::iter::repeat_with(|| vec![Tile::Empty; bounds.x])
-        .take(bounds.y)
-        .collect();
-    Ok(Map { bounds, tiles, seq })
-}
-
-type G = Graph<Pos<usize>, ()>;
-
-fn add_edges_for(m: &Map, u: Point, g: &mut G, reciprocal: bool) -> Result<()> {
-    Ok(for dir in enum_iterator::all::<Dir>() {
-        if let Some(v) = m.go_bounded(&u, dir) {
-            match m.get(v) {
-                Tile::Empty => {
-                    g.add_edge(Edge::with_weight(u, v, 1.))
-                        .map_err(graphrs_anyhow)?;
-                    if reciprocal {
-                        g.add_edge(Edge::with_weight(v, u, 1.))
-                            .map_err(graphrs_anyhow)?;
-                    }
-                }
-                _ => {}
-            }
-        }
-    })
+    let tiles = Grid::new(bounds, Tile::Empty);
+    Ok(Map { tiles, seq })
 }
 
-fn compose_graph(m: &Map) -> Result<G> {
-    let mut g: G = Graph::new(GraphSpecs {
-        directed: true,
-        edge_dedupe_strategy: graphrs::EdgeDedupeStrategy::KeepFirst,
-        missing_node_strategy: graphrs::MissingNodeStrategy::Create,
-        multi_edges: false,
-        self_loops: false,
-        self_loops_false_strategy: graphrs::SelfLoopsFalseStrategy::Error,
-    });
-    for u in m.bounds.clone().generator() {
-        add_edges_for(m, u, &mut g, false)?;
-    }
-    Ok(g)
-}
-
-fn part1_inner(lines: Vec<String>, bounds: Point, n: usize) -> Result<usize> {
-    let mut m = parse(lines, bounds)?;
+fn part1_inner(input: &Input, bounds: Point, n: usize) -> Result<usize> {
+    let mut m = parsing(|| parse(input, bounds))?;
     {
         let points = m.seq.clone().into_iter().take(n);
         for p in points {
@@ -120,65 +86,93 @@ fn part1_inner(lines: Vec<String>, bounds: Point, n: usize) -> Result<usize> {
     let start = Point::default();
     let end = bounds - Point::one();
 
-    let g = compose_graph(&m)?;
-
-    let sp = dijkstra::single_source(&g, true, start, Some(end), None, true, true)
-        .map_err(graphrs_anyhow)?;
-    if let Some(info) = sp.get(&end) {
-        println!("from {} = {}", start, info.distance);
-        let first = info.paths.first().unwrap();
-        for f in first {
-            m.set(*f, Tile::Mark);
-        }
-        // println!("{:?}", first);
-        println!("{}", m);
-        Ok(first.len() - 1) // -1 for steps, not nodes
-    } else {
+    let Some(path) = m.shortest_path(start, end) else {
         bail!("no path!")
+    };
+    debug!("from {} = {}", start, path.len() - 1);
+    for f in path.iter() {
+        m.set(*f, Tile::Mark);
     }
+    trace!("{:?}", path);
+    debug!("map:\n{}", m.tiles);
+    Ok(path.len() - 1) // -1 for steps, not nodes
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let bounds = Point { x: 71, y: 71 };
-    let total = part1_inner(lines, bounds, 1024)?;
+pub fn part1(input: &Input, bounds: Point, bytes: usize) -> Result<String> {
+    let total = part1_inner(input, bounds, bytes)?;
     Ok(total.to_string())
 }
 
-fn part2_inner(lines: Vec<String>, bounds: Point) -> Result<Point> {
-    let mut m = parse(lines, bounds)?;
+fn part2_inner(input: &Input, bounds: Point) -> Result<Point> {
+    let mut m = parsing(|| parse(input, bounds))?;
     let start = Point::default();
     let end = bounds - Point::one();
 
-    // I really wish graphrs had a "remove edge", but alas. Go in reverse!
-    // Start with all points filled
+    let mut path: HashSet<Point> = m
+        .shortest_path(start, end)
+        .ok_or_else(|| anyhow!("no path!"))?
+        .into_iter()
+        .collect();
     for p in m.seq.clone() {
         m.set(p, Tile::Wall);
-    }
-
-    // Connect initial empty set:
-    let mut g = compose_graph(&m)?;
-    // then, going in reverse, remove a wall
-    for p in m.seq.clone().into_iter().rev() {
-        m.set(p, Tile::Empty);
-        add_edges_for(&m, p, &mut g, true)?;
-        // does this open a path?
-        let bfs = g.breadth_first_search(&start);
-        if bfs.contains(&end) {
-            // then it was this point that cut it off
-            m.set(p, Tile::Mark);
-            println!("{}", m);
-            return Ok(p);
+        // Only a byte landing on the current path can cut it off
+        if !path.contains(&p) {
+            continue;
+        }
+        match m.shortest_path(start, end) {
+            Some(detour) => path = detour.into_iter().collect(),
+            None => {
+                m.set(p, Tile::Mark);
+                debug!("map:\n{}", m.tiles);
+                return Ok(p);
+            }
         }
     }
     bail!("no cut-offs?")
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let bounds = Point { x: 71, y: 71 };
-    let cutoff = part2_inner(lines, bounds)?;
+pub fn part2(input: &Input, bounds: Point) -> Result<String> {
+    let cutoff = part2_inner(input, bounds)?;
     Ok(format!("{},{}", cutoff.x, cutoff.y))
 }
 
+pub struct Day18;
+impl Solver for Day18 {
+    fn day(&self) -> u8 {
+        18
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, bounds(params)?, params.get("bytes")?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, bounds(params)?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "size",
+                default: "71",
+                help: "width and height of the memory space",
+                check: Param::at_least::<1>,
+            },
+            Param {
+                key: "bytes",
+                default: "1024",
+                help: "bytes fallen before part 1 starts walking",
+                check: Param::parses::<usize>,
+            },
+        ]
+    }
+}
+
+fn bounds(params: &Params) -> Result<Point> {
+    let size = params.get("size")?;
+    Ok(Point { x: size, y: size })
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -186,13 +180,9 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             5,4
             4,2
             4,5
@@ -219,14 +209,14 @@ mod test {
             1,6
             2,0
         "});
-        let steps = part1_inner(lines, Point { x: 7, y: 7 }, 12)?;
+        let steps = part1_inner(&input, Point { x: 7, y: 7 }, 12)?;
         assert_eq!(steps, 22);
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             5,4
             4,2
             4,5
@@ -253,7 +243,7 @@ mod test {
             1,6
             2,0
         "});
-        let steps = part2_inner(lines, Point { x: 7, y: 7 })?;
+        let steps = part2_inner(&input, Point { x: 7, y: 7 })?;
         assert_eq!(steps, Point { x: 6, y: 1 });
         Ok(())
     }
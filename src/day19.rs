@@ -1,7 +1,11 @@
 use anyhow::Result;
+use log::trace;
 use regex::Regex;
 use std::collections::{HashMap, HashSet};
 
+use crate::common::Input;
+use crate::solver::{parsing, Params, Solver};
+
 #[derive(Debug)]
 struct Chal<'a> {
     patterns: Vec<&'a str>,
@@ -11,11 +15,10 @@ struct Chal<'a> {
 }
 
 impl<'a> Chal<'a> {
-    fn parse(lines: &'a Vec<String>) -> Result<Chal> {
-        let mut iter = lines.into_iter();
-        let patterns: Vec<&str> = iter.next().unwrap().split(", ").collect();
-        iter.next();
-        let designs = iter.map(|s| s.as_ref()).collect();
+    fn parse(sections: &'a [Input; 2]) -> Result<Chal<'a>> {
+        let [patterns, designs] = sections;
+        let patterns: Vec<&str> = patterns.line()?.text.split(", ").collect();
+        let designs = designs.lines().collect();
         let mut pat_idx = HashSet::new();
         let mut max_len = 0;
         for p in &patterns {
@@ -31,8 +34,9 @@ impl<'a> Chal<'a> {
     }
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let c = Chal::parse(&lines)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let sections = parsing(|| input.expect_sections(["patterns", "designs"]))?;
+    let c = parsing(|| Chal::parse(&sections))?;
     let re = {
         let all_pats = c.patterns.join("|");
         let giga_re_str = format!("^({})+$", all_pats);
@@ -40,7 +44,7 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
     };
     let mut total = 0;
     for d in c.designs {
-        if re.is_match(&d) {
+        if re.is_match(d) {
             total += 1;
         }
     }
@@ -48,18 +52,16 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
 }
 
 fn rec_find<'a>(d: &'a str, c: &Chal, cache: &mut HashMap<&'a str, usize>) -> usize {
-    if d.len() == 1 && c.pat_idx.contains(d) {
-        return 1;
-    } else if d.len() == 0 {
+    if d.is_empty() || (d.len() == 1 && c.pat_idx.contains(d)) {
         return 1;
     }
 
     if let Some(cached) = cache.get(d) {
-        // println!("cached {} {}", d, cached);
+        trace!("cached {} {}", d, cached);
         return *cached;
     }
 
-    // println!("fresh {}", d);
+    trace!("fresh {}", d);
     let mut total = 0;
     let max = c.max_len.min(d.len()) - 1; // only look for prefixes up to the max pattern length
     for n in 0..=max {
@@ -68,17 +70,18 @@ fn rec_find<'a>(d: &'a str, c: &Chal, cache: &mut HashMap<&'a str, usize>) -> us
         if c.pat_idx.contains(prefix) {
             let suffix = &d[n + 1..];
             let add = rec_find(suffix, c, cache);
-            // println!("found {} = {}|{}", add, prefix, suffix);
+            trace!("found {} = {}|{}", add, prefix, suffix);
             total += add;
         }
     }
-    // println!("done {} = {}", total, d);
+    trace!("done {} = {}", total, d);
     cache.insert(d, total);
     total
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let c = Chal::parse(&lines)?;
+pub fn part2(input: &Input) -> Result<String> {
+    let sections = parsing(|| input.expect_sections(["patterns", "designs"]))?;
+    let c = parsing(|| Chal::parse(&sections))?;
     let all: usize = c
         .designs
         .iter()
@@ -90,6 +93,21 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(all.to_string())
 }
 
+pub struct Day19;
+impl Solver for Day19 {
+    fn day(&self) -> u8 {
+        19
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -97,13 +115,9 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             r, wr, b, g, bwu, rb, gb, br
 
             brwrr
@@ -115,46 +129,46 @@ mod test {
             brgr
             bbrgwb
         "});
-        assert_eq!(part1(lines)?, "6");
+        assert_eq!(part1(&input)?, "6");
         Ok(())
     }
 
     #[test]
     fn test_part2_a() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             r, wr, b, g, bwu, rb, gb, br
 
             brwrr
         "});
-        assert_eq!(part2(lines)?, "2");
+        assert_eq!(part2(&input)?, "2");
         Ok(())
     }
 
     #[test]
     fn test_part2_b() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             r, wr, b, g, bwu, rb, gb, br
 
             bggr
         "});
-        assert_eq!(part2(lines)?, "1");
+        assert_eq!(part2(&input)?, "1");
         Ok(())
     }
 
     #[test]
     fn test_part2_c() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             r, wr, b, g, bwu, rb, gb, br
 
             gbbr
         "});
-        assert_eq!(part2(lines)?, "4");
+        assert_eq!(part2(&input)?, "4");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             r, wr, b, g, bwu, rb, gb, br
 
             brwrr
@@ -166,7 +180,7 @@ mod test {
             brgr
             bbrgwb
         "});
-        assert_eq!(part2(lines)?, "16");
+        assert_eq!(part2(&input)?, "16");
         Ok(())
     }
 }
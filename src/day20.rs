@@ -1,9 +1,11 @@
 use std::collections::HashSet;
 
-use anyhow::{bail, Result};
-use graphrs::{algorithms::shortest_path::dijkstra, Edge, Graph, GraphSpecs};
+use anyhow::{anyhow, Result};
+use log::trace;
 
-use crate::common::{graphrs_anyhow, Dir, Pos};
+use crate::common::search::bfs;
+use crate::common::{Dir, Grid, Input, Pos, Render};
+use crate::solver::{parsing, Param, Params, Solver};
 type Point = Pos<usize>;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -14,10 +16,20 @@ enum Tile {
     End,
 }
 
+impl Render for Tile {
+    fn render(&self) -> char {
+        match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Start => 'S',
+            Tile::End => 'E',
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 struct Map {
-    bounds: Point,
-    tiles: Vec<Vec<Tile>>,
+    tiles: Grid<Tile>,
     open: HashSet<Point>,
     start: Point,
     end: Point,
@@ -25,106 +37,69 @@ struct Map {
 
 impl Map {
     fn get(&self, p: Point) -> Tile {
-        self.tiles[p.y][p.x]
+        self.tiles[p]
     }
 
     fn in_main_bounds(&self, p: &Point) -> bool {
-        p.x > 0 && p.x < (self.bounds.x - 1) && p.y > 0 && p.y < (self.bounds.y - 1)
+        let bounds = self.tiles.bounds();
+        p.x > 0 && p.x < (bounds.x - 1) && p.y > 0 && p.y < (bounds.y - 1)
     }
 }
 
-fn parse(lines: Vec<String>) -> Result<Map> {
-    let mut start = Point::default();
-    let mut end = Point::default();
-    let mut tiles = vec![];
-    let mut open = HashSet::new();
-    for (y, line_str) in lines.into_iter().enumerate() {
-        let mut row = vec![];
-        for (x, c) in line_str.chars().enumerate() {
-            let p = Point { x, y };
-            row.push(match c {
-                '#' => Tile::Wall,
-                '.' => {
-                    open.insert(p);
-                    Tile::Empty
-                }
-                'S' => {
-                    open.insert(p);
-                    start = p;
-                    Tile::Start
-                }
-                'E' => {
-                    open.insert(p);
-                    end = p;
-                    Tile::End
-                }
-                _ => bail!("invalid tile {}", c),
-            });
-        }
-        tiles.push(row)
-    }
-    let bounds = Point {
-        x: tiles.first().unwrap().len(),
-        y: tiles.len(),
-    };
+fn parse(input: &Input) -> Result<Map> {
+    let tiles = input.grid_with(|c| {
+        Ok(match c {
+            '#' => Tile::Wall,
+            '.' => Tile::Empty,
+            'S' => Tile::Start,
+            'E' => Tile::End,
+            _ => return Err(format!("invalid tile {:?}", c)),
+        })
+    })?;
+    let start = tiles.find('S').ok_or_else(|| anyhow!("missing start"))?;
+    let end = tiles.find('E').ok_or_else(|| anyhow!("missing end"))?;
+    let open = tiles
+        .iter()
+        .filter(|(_, tile)| **tile != Tile::Wall)
+        .map(|(p, _)| p)
+        .collect();
     Ok(Map {
         tiles,
         open,
-        bounds,
         start,
         end,
     })
 }
 
-type G = Graph<Point, ()>;
-
-fn compose_graph(m: &Map) -> Result<G> {
-    let mut g: G = Graph::new(GraphSpecs {
-        directed: false,
-        edge_dedupe_strategy: graphrs::EdgeDedupeStrategy::KeepFirst,
-        missing_node_strategy: graphrs::MissingNodeStrategy::Create,
-        multi_edges: false,
-        self_loops: false,
-        self_loops_false_strategy: graphrs::SelfLoopsFalseStrategy::Error,
-    });
-    for u in m.open.iter() {
-        for dir in enum_iterator::all::<Dir>() {
-            let v = u.go(dir);
-            match m.get(v) {
-                Tile::Empty | Tile::End | Tile::Start => {
-                    g.add_edge(Edge::with_weight(*u, v, 1.))
-                        .map_err(graphrs_anyhow)?;
-                }
-                _ => {}
-            }
-        }
-    }
-    Ok(g)
-}
-
-type Distances = Vec<Vec<usize>>;
+type Distances = Grid<usize>;
 fn calc_distances(m: &Map) -> Result<(Distances, Distances, usize)> {
-    let g = compose_graph(m)?;
+    let neighbours = |u: &Point| {
+        m.tiles
+            .neighbours(*u)
+            .filter(|(_, v)| m.get(*v) != Tile::Wall)
+            .map(|(_, v)| v)
+    };
     // all-paths is overkill; just compute distances from all other notes to both start and end
-    let for_start = dijkstra::single_source(&g, true, m.start, None, None, false, false)
-        .map_err(graphrs_anyhow)?;
-    let for_end = dijkstra::single_source(&g, true, m.end, None, None, false, false)
-        .map_err(graphrs_anyhow)?;
+    let for_start = bfs(m.start, neighbours);
+    let for_end = bfs(m.end, neighbours);
 
-    let repeater = std::iter::repeat_with(|| vec![usize::MAX; m.bounds.x]);
-    let mut start_dist: Distances = repeater.take(m.bounds.y).collect();
-    let mut end_dist: Distances = repeater.take(m.bounds.y).collect();
+    let mut start_dist: Distances = Grid::new(m.tiles.bounds(), usize::MAX);
+    let mut end_dist: Distances = Grid::new(m.tiles.bounds(), usize::MAX);
     for p in m.open.iter() {
-        start_dist[p.y][p.x] = for_start.get(p).expect("from-start present").distance as usize;
-        end_dist[p.y][p.x] = for_end.get(p).expect("from-end present").distance as usize;
+        start_dist[*p] = for_start
+            .cost(p)
+            .ok_or_else(|| anyhow!("{} unreachable from start", p))?;
+        end_dist[*p] = for_end
+            .cost(p)
+            .ok_or_else(|| anyhow!("{} unreachable from end", p))?;
     }
-    let main_time = start_dist[m.end.y][m.end.x];
+    let main_time = start_dist[m.end];
     Ok((start_dist, end_dist, main_time))
 }
 
-pub fn part1_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
+pub fn part1(input: &Input, cutoff: usize) -> Result<String> {
     let mut total = 0;
-    let m = parse(lines)?;
+    let m = parsing(|| parse(input))?;
     let (start_dist, end_dist, main_time) = calc_distances(&m)?;
 
     let mut visited: HashSet<Point> = HashSet::new();
@@ -143,26 +118,23 @@ pub fn part1_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
                 // U-shaped or headed "backwards"
                 continue;
             }
-            match (m.get(p1), m.get(p2)) {
-                (Tile::Wall, Tile::Empty | Tile::End | Tile::Start) => {
-                    // can knock out p1
-                    let start_p0 = start_dist[p0.y][p0.x];
-                    let p2_end = end_dist[p2.y][p2.x];
-                    let time = start_p0 + 2 + p2_end;
-                    if main_time > time && main_time - time >= cutoff {
-                        // println!(
-                        //     "wallhack: {}[{}]->{}->{}[{}] {}",
-                        //     p0,
-                        //     start_p0,
-                        //     p1,
-                        //     p2,
-                        //     p2_end,
-                        //     (main_time - time)
-                        // );
-                        total += 1;
-                    }
+            if let (Tile::Wall, Tile::Empty | Tile::End | Tile::Start) = (m.get(p1), m.get(p2)) {
+                // can knock out p1
+                let start_p0 = start_dist[*p0];
+                let p2_end = end_dist[p2];
+                let time = start_p0 + 2 + p2_end;
+                if main_time > time && main_time - time >= cutoff {
+                    trace!(
+                        "wallhack: {}[{}]->{}->{}[{}] {}",
+                        p0,
+                        start_p0,
+                        p1,
+                        p2,
+                        p2_end,
+                        (main_time - time)
+                    );
+                    total += 1;
                 }
-                _ => {}
             }
         }
     }
@@ -170,15 +142,9 @@ pub fn part1_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
     Ok(total.to_string())
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    part1_inner(lines, 100)
-}
-
-const MAX_HACK: usize = 20;
-
-pub fn part2_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
+pub fn part2(input: &Input, cutoff: usize, max_cheat: usize) -> Result<String> {
     let mut total = 0;
-    let m = parse(lines)?;
+    let m = parsing(|| parse(input))?;
     let (start_dist, end_dist, main_time) = calc_distances(&m)?;
     let mut visited: HashSet<Point> = HashSet::new();
     // visit all empty spaces and see if there's a shortcut
@@ -187,25 +153,25 @@ pub fn part2_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
             continue;
         }
         for p1 in m.open.iter() {
-            let d = p0.manhattan(&p1);
-            if d == 0 || MAX_HACK < d {
+            let d = p0.manhattan(p1);
+            if d == 0 || max_cheat < d {
                 continue;
             }
             match m.get(*p1) {
                 Tile::Empty | Tile::End | Tile::Start => {
-                    let start_p0 = start_dist[p0.y][p0.x];
-                    let p1_end = end_dist[p1.y][p1.x];
+                    let start_p0 = start_dist[*p0];
+                    let p1_end = end_dist[*p1];
                     let time = start_p0 + d + p1_end;
                     if main_time > time && main_time - time >= cutoff {
-                        // println!(
-                        //     "cheat: {}[{}]->{}[{}] d{}, {}",
-                        //     p0,
-                        //     start_p0,
-                        //     p1,
-                        //     p1_end,
-                        //     d,
-                        //     (main_time - time)
-                        // );
+                        trace!(
+                            "cheat: {}[{}]->{}[{}] d{}, {}",
+                            p0,
+                            start_p0,
+                            p1,
+                            p1_end,
+                            d,
+                            (main_time - time)
+                        );
                         total += 1;
                     }
                 }
@@ -216,8 +182,36 @@ pub fn part2_inner(lines: Vec<String>, cutoff: usize) -> Result<String> {
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    part2_inner(lines, 100)
+pub struct Day20;
+impl Solver for Day20 {
+    fn day(&self) -> u8 {
+        20
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, params.get("cutoff")?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, params.get("cutoff")?, params.get("max_cheat")?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "cutoff",
+                default: "100",
+                help: "fewest picoseconds a cheat must save to count",
+                check: Param::parses::<usize>,
+            },
+            Param {
+                key: "max_cheat",
+                default: "20",
+                help: "longest cheat in part 2, in picoseconds",
+                check: Param::parses::<usize>,
+            },
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -227,38 +221,35 @@ mod test {
 
     use super::*;
 
-    fn lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
     #[test]
     fn test_part1_ezer() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #####
             #...#
             #.#.#
             #S#E#
             #####
         "});
-        assert_eq!(part1_inner(lines, 1)?, (2).to_string());
+        assert_eq!(part1(&input, 1)?, (2).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part1_ez() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             #########
             #.......#
             #.#.###.#
             #S#.#E..#
             #########
         "});
-        assert_eq!(part1_inner(lines, 1)?, (3).to_string());
+        assert_eq!(part1(&input, 1)?, (3).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ###############
             #...#...#.....#
             #.#.#.#.#.###.#
@@ -276,16 +267,16 @@ mod test {
             ###############
         "});
         assert_eq!(
-            part1_inner(lines.clone(), 1)?,
+            part1(&input.clone(), 1)?,
             (14 + 14 + 2 + 4 + 2 + 3 + 5).to_string()
         );
-        assert_eq!(part1_inner(lines, 64)?, "1");
+        assert_eq!(part1(&input, 64)?, "1");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        let lines = lines(indoc! {"
+        let input = Input::new(indoc! {"
             ###############
             #...#...#.....#
             #.#.#.#.#.###.#
@@ -303,12 +294,12 @@ mod test {
             ###############
         "});
         assert_eq!(
-            part2_inner(lines.clone(), 50)?,
+            part2(&input.clone(), 50, 20)?,
             (32 + 31 + 29 + 39 + 25 + 23 + 20 + 19 + 12 + 14 + 12 + 22 + 4 + 3).to_string()
         );
-        assert_eq!(part2_inner(lines.clone(), 72)?, "29");
-        assert_eq!(part2_inner(lines.clone(), 74)?, "7");
-        assert_eq!(part2_inner(lines.clone(), 76)?, "3");
+        assert_eq!(part2(&input.clone(), 72, 20)?, "29");
+        assert_eq!(part2(&input.clone(), 74, 20)?, "7");
+        assert_eq!(part2(&input.clone(), 76, 20)?, "3");
         Ok(())
     }
 }
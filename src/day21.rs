@@ -2,7 +2,8 @@ use anyhow::{bail, Result};
 use itertools::Itertools;
 use memoize::memoize;
 
-use crate::common::Pos;
+use crate::common::{Input, Line, ParseError, Pos};
+use crate::solver::{parsing, Params, Solver};
 type Point = Pos<isize>;
 
 static KP_A: Point = Point { x: 2, y: 3 };
@@ -29,10 +30,10 @@ enum DP {
 fn gen_raw_steps(from: Point, to: Point) -> Vec<DP> {
     let mut raw_steps = vec![];
     let d = to - from;
-    for _ in 0..(d.y.abs() as usize) {
+    for _ in 0..d.y.unsigned_abs() {
         raw_steps.push(if d.y < 0 { DP::U } else { DP::D });
     }
-    for _ in 0..(d.x.abs() as usize) {
+    for _ in 0..d.x.unsigned_abs() {
         raw_steps.push(if d.x < 0 { DP::L } else { DP::R });
     }
     raw_steps
@@ -143,26 +144,53 @@ fn route_keypad(kp_keys: &str, max_depth: usize) -> Result<usize> {
     Ok(total)
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
+/// The numeric part of a door code like `029A`
+fn parse_code(line: Line) -> Result<usize, ParseError> {
+    if let Some(i) = line.text.find(|c: char| !c.is_ascii_digit() && c != 'A') {
+        let col = line.text[..i].chars().count();
+        return Err(line.error_at(col, "invalid keypad key"));
+    }
+    match line.text.strip_suffix('A') {
+        Some(digits) if !digits.is_empty() && !digits.contains('A') => line.parse(digits),
+        _ => Err(line.error("expected a door code like `029A`")),
+    }
+}
+
+pub fn part1(input: &Input) -> Result<String> {
     let mut total: usize = 0;
-    for line in lines {
-        let numeric = line.chars().take(3).join("").parse::<usize>()?;
-        let presses = route_keypad(&line, 2)?;
+    for line in input.numbered_lines() {
+        let numeric = parsing(|| parse_code(line))?;
+        let presses = route_keypad(line.text, 2)?;
         total += presses * numeric;
     }
     Ok(total.to_string())
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
+pub fn part2(input: &Input) -> Result<String> {
     let mut total: usize = 0;
-    for line in lines {
-        let numeric = line.chars().take(3).join("").parse::<usize>()?;
-        let presses = route_keypad(&line, 25)?;
+    for line in input.numbered_lines() {
+        let numeric = parsing(|| parse_code(line))?;
+        let presses = route_keypad(line.text, 25)?;
         total += presses * numeric;
     }
     Ok(total.to_string())
 }
 
+pub struct Day21;
+impl Solver for Day21 {
+    fn day(&self) -> u8 {
+        21
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anyhow::Result;
@@ -170,38 +198,34 @@ mod test {
 
     use super::*;
 
-    fn to_lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1_a() -> Result<()> {
-        let lines = to_lines(indoc! {"
+        let input = Input::new(indoc! {"
             179A
         "});
-        assert_eq!(part1(lines)?, (68 * 179).to_string());
+        assert_eq!(part1(&input)?, (68 * 179).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part1_b() -> Result<()> {
-        let lines = to_lines(indoc! {"
+        let input = Input::new(indoc! {"
             379A
         "});
-        assert_eq!(part1(lines)?, (64 * 379).to_string());
+        assert_eq!(part1(&input)?, (64 * 379).to_string());
         Ok(())
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        let lines = to_lines(indoc! {"
+        let input = Input::new(indoc! {"
             029A
             980A
             179A
             456A
             379A
         "});
-        assert_eq!(part1(lines)?, "126384");
+        assert_eq!(part1(&input)?, "126384");
         Ok(())
     }
 }
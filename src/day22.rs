@@ -1,23 +1,35 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use log::{debug, info, trace};
 use rayon::prelude::*;
 
+use crate::common::Input;
+use crate::solver::{parsing, Param, Params, Solver};
+
 const MASK_PRUNE: i32 = 0xFFFFFF;
 
 fn turn(x: i32) -> i32 {
     let x = ((x << 6) ^ x) & MASK_PRUNE;
     let x = ((x >> 5) ^ x) & MASK_PRUNE;
-    let x = ((x << 11) ^ x) & MASK_PRUNE;
-    x
+
+    ((x << 11) ^ x) & MASK_PRUNE
+}
+
+/// Each buyer's initial secret, one per line
+fn parse(input: &Input) -> Result<Vec<i32>> {
+    input
+        .numbered_lines()
+        .map(|line| Ok(line.parse(line.text)?))
+        .collect()
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let mut state: Vec<i32> = lines.into_iter().map(|y| y.parse().unwrap()).collect();
-    for _n in 0usize..2000 {
-        for i in 0usize..state.len() {
-            state[i] = turn(state[i])
+pub fn part1(input: &Input, secrets: usize) -> Result<String> {
+    let mut state: Vec<i32> = parsing(|| parse(input))?;
+    for _n in 0usize..secrets {
+        for s in state.iter_mut() {
+            *s = turn(*s)
         }
     }
-    println!("states: {:?}", state);
+    debug!("states: {:?}", state);
     Ok(state
         .into_iter()
         .map(|x| x as isize)
@@ -25,15 +37,16 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
         .to_string())
 }
 
-const N: usize = 2000;
-
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut state: Vec<i32> = lines.into_iter().map(|y| y.parse().unwrap()).collect();
+pub fn part2(input: &Input, secrets: usize) -> Result<String> {
+    if secrets < 3 {
+        bail!("part 2 needs at least 3 secrets, got {}", secrets);
+    }
+    let mut state: Vec<i32> = parsing(|| parse(input))?;
     let k = state.len(); // # of sellers
 
     let mut bananas: Vec<Vec<i8>> = vec![state.iter().map(|x| (x % 10) as i8).collect()]; // n row, k col
     let mut delta_b: Vec<Vec<i8>> = vec![vec![0; k]]; // n row, k col
-    for n in 1usize..=N {
+    for n in 1usize..=secrets {
         let mut banana_row: Vec<i8> = vec![0; k];
         let mut delta_b_row: Vec<i8> = vec![0; k];
         let prev_banana_row = &bananas[n - 1];
@@ -47,35 +60,35 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
         bananas.push(banana_row);
         delta_b.push(delta_b_row);
     }
-    // println!("zero row: {:?}", bananas[0]);
-    // println!("first row: {:?}", bananas[1]);
-    // println!("last row: {:?}", bananas[N]);
-    // println!("delta-b zero row: {:?}", delta_b[0]);
-    // println!("delta-b 1st row: {:?}", delta_b[1]);
-    // println!("delta-b Nth row: {:?}", delta_b[N]);
+    debug!("zero row: {:?}", bananas[0]);
+    debug!("first row: {:?}", bananas[1]);
+    debug!("last row: {:?}", bananas[secrets]);
+    debug!("delta-b zero row: {:?}", delta_b[0]);
+    debug!("delta-b 1st row: {:?}", delta_b[1]);
+    debug!("delta-b Nth row: {:?}", delta_b[secrets]);
 
     // transpose into rows per seller
-    let mut seller_history: Vec<Vec<i8>> = std::iter::repeat_with(|| vec![0i8; N + 1])
+    let mut seller_history: Vec<Vec<i8>> = std::iter::repeat_with(|| vec![0i8; secrets + 1])
         .take(k)
         .collect();
-    for n in 1..=N {
+    for n in 1..=secrets {
         for seller in 0..k {
             seller_history[seller][n] = delta_b[n][seller];
         }
     }
-    println!("generated price history");
+    debug!("generated price history");
 
     let mut best = 0;
     let mut best_seq = vec![];
 
     for a in -9..=9 {
-        println!("scanning {a}");
+        debug!("scanning {a}");
         // build an index of where `a` occurs for each seller
-        let mut a_index: Vec<Vec<usize>> = std::iter::repeat_with(|| vec![]).take(k).collect();
+        let mut a_index: Vec<Vec<usize>> = std::iter::repeat_with(Vec::new).take(k).collect();
         for seller in 0..k {
             let hist = &seller_history[seller];
-            for n in 1..=(N - 3) {
-                if hist[n] == a {
+            for (n, delta) in hist.iter().enumerate().take(secrets - 2).skip(1) {
+                if *delta == a {
                     a_index[seller].push(n);
                 }
             }
@@ -95,11 +108,12 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
                                 let start = n + 1; // offset of `b` since `n` is known `a`
                                 let end = n + 3;
                                 let window = &hist[start..=end];
-                                if window == &trial {
-                                    // println!(
-                                    //     "{:?} match {end} for {seller}: {}",
-                                    //     trial, bananas[end][seller]
-                                    // );
+                                if window == trial {
+                                    trace!(
+                                        "{:?} match {end} for {seller}: {}",
+                                        trial,
+                                        bananas[end][seller]
+                                    );
                                     return bananas[end][seller] as usize;
                                 }
                             }
@@ -116,10 +130,34 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
         }
     }
 
-    println!("best seq: {:?}", best_seq);
+    info!("best seq: {:?}", best_seq);
     Ok(best.to_string())
 }
 
+pub struct Day22;
+impl Solver for Day22 {
+    fn day(&self) -> u8 {
+        22
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(input, params.get("secrets")?)
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(input, params.get("secrets")?)
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[Param {
+            key: "secrets",
+            default: "2000",
+            help: "new secret numbers each buyer generates",
+            check: Param::parses::<usize>,
+        }]
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -128,13 +166,9 @@ mod test {
 
     use super::*;
 
-    fn to_lines(text: &str) -> Vec<String> {
-        text.lines().map(|x| x.to_string()).collect()
-    }
-
     #[test]
     fn test_part1_first_steps() -> Result<()> {
-        let expect = to_lines(indoc! {"
+        let expect = Input::new(indoc! {"
             15887950
             16495136
             527345
@@ -146,8 +180,7 @@ mod test {
             7753432
             5908254
         "})
-        .into_iter()
-        .map(|y| y.parse::<i32>().unwrap());
+        .numbers::<i32>()?;
         let mut x = 123;
         for y in expect {
             x = turn(x);
@@ -156,40 +189,51 @@ mod test {
         Ok(())
     }
 
-    // #[test]
-    // fn test_period() -> Result<()> {
-    //     let mut seen = HashSet::new();
-    //     let mut x = 123;
-    //     seen.insert(x);
-    //     for n in 0usize.. {
-    //         x = turn(x);
-    //         if !seen.insert(x) {
-    //             println!("period {}", n);
-    //             break;
-    //         }
-    //     }
-    //     Ok(())
-    // }
-
     #[test]
     fn test_part1() {
-        let lines = to_lines(indoc! {"
+        let input = Input::new(indoc! {"
             1
             10
             100
             2024
         "});
-        assert_eq!(part1(lines).unwrap(), "37327623");
+        assert_eq!(part1(&input, 2000).unwrap(), "37327623");
     }
 
     #[test]
     fn test_part2() {
-        let lines = to_lines(indoc! {"
+        let input = Input::new(indoc! {"
             1
             2
             3
             2024
         "});
-        assert_eq!(part2(lines).unwrap(), "23");
+        assert_eq!(part2(&input, 2000).unwrap(), "23");
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let e = parse(&Input::new("1\n2x\n3")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            indoc! {r#"
+                line 2, column 1: invalid number "2x": invalid digit found in string
+                   2 | 2x
+                     | ^^"#}
+        );
+    }
+
+    #[test]
+    fn test_too_few_secrets() -> Result<()> {
+        // Any number will do for part 1, only part 2 needs 3 or more
+        let params = Params::with_overrides(Day22.params(), &["secrets=1"])?;
+        let input = Input::new("1\n2");
+        assert_eq!(
+            Day22.part1(&input, &params)?,
+            (turn(1) + turn(2)).to_string()
+        );
+        let e = Day22.part2(&input, &params).unwrap_err();
+        assert_eq!(e.to_string(), "part 2 needs at least 3 secrets, got 1");
+        Ok(())
     }
 }
@@ -1,45 +1,47 @@
 use std::collections::{BTreeSet, HashMap, HashSet};
 
 use anyhow::Result;
-use graphrs::{Edge, Graph, GraphSpecs};
 use itertools::Itertools;
+use log::trace;
 
-use crate::common::*;
+use crate::common::Input;
+use crate::solver::{parsing, Params, Solver};
 
 struct Chal<'a> {
     t_nodes: HashSet<&'a str>,
-    g: Graph<&'a str, ()>,
+    adj: HashMap<&'a str, HashSet<&'a str>>,
 }
 
 impl<'a> Chal<'a> {
-    fn parse(lines: &'a Vec<String>) -> Result<Self> {
-        let mut g: Graph<&'a str, _> = Graph::new(GraphSpecs::undirected_create_missing());
+    fn parse(input: &'a Input) -> Result<Self> {
+        let mut adj = HashMap::new();
         let mut t_nodes = HashSet::new();
-        for line in lines {
-            let (a, b) = line.split_once('-').unwrap();
+        for line in input.numbered_lines() {
+            let (a, b) = line.split_once("-", "`a-b`")?;
             if a.starts_with('t') {
                 t_nodes.insert(a);
             }
             if b.starts_with('t') {
                 t_nodes.insert(b);
             }
-            g.add_edge(Edge::new(a, b)).map_err(graphrs_anyhow)?;
+            adj.entry(a).or_insert_with(HashSet::new).insert(b);
+            adj.entry(b).or_insert_with(HashSet::new).insert(a);
         }
 
-        Ok(Chal { t_nodes, g })
+        Ok(Chal { t_nodes, adj })
     }
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let c = Chal::parse(&lines)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let c = parsing(|| Chal::parse(input))?;
     let mut triples: HashSet<String> = HashSet::new();
     for t_node in c.t_nodes {
-        let conn = c.g.get_neighbor_nodes(t_node).map_err(graphrs_anyhow)?;
+        let conn = &c.adj[t_node];
         for (u, v) in conn.iter().tuple_combinations() {
-            if c.g.get_edge(u.name, v.name).is_ok() {
-                let mut trip_name = vec![t_node, u.name, v.name];
+            if c.adj[u].contains(v) {
+                let mut trip_name = [t_node, *u, *v];
                 trip_name.sort();
-                // println!("found {:?}", trip_name);
+                trace!("found {:?}", trip_name);
                 triples.insert(trip_name.join(","));
             }
         }
@@ -51,25 +53,25 @@ struct Chal2<'a> {
     adj: HashMap<&'a str, HashSet<&'a str>>,
 }
 impl<'a> Chal2<'a> {
-    fn parse(lines: &'a Vec<String>) -> Result<Self> {
+    fn parse(input: &'a Input) -> Result<Self> {
         let mut adj = HashMap::new();
-        for line in lines.iter() {
-            let (a, b) = line.split_once('-').unwrap();
-            adj.entry(a).or_insert_with(|| HashSet::new()).insert(b);
-            adj.entry(b).or_insert_with(|| HashSet::new()).insert(a);
+        for line in input.numbered_lines() {
+            let (a, b) = line.split_once("-", "`a-b`")?;
+            adj.entry(a).or_insert_with(HashSet::new).insert(b);
+            adj.entry(b).or_insert_with(HashSet::new).insert(a);
         }
         Ok(Self { adj })
     }
 }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let c = Chal2::parse(&lines)?;
+pub fn part2(input: &Input) -> Result<String> {
+    let c = parsing(|| Chal2::parse(input))?;
     let mut max_clique = "".to_owned();
     for (root, root_adj) in c.adj.iter() {
         // Clone a working-set of adjacent nodes
         let mut work_adj = root_adj.clone();
         // Go until all neighbours visited
-        while work_adj.len() > 0 {
+        while !work_adj.is_empty() {
             // Clique has just the root initially
             let mut non_clique: HashSet<&str> = HashSet::new();
             let mut clique: BTreeSet<&str> = BTreeSet::new();
@@ -103,14 +105,29 @@ pub fn part2(lines: Vec<String>) -> Result<String> {
     Ok(max_clique)
 }
 
+pub struct Day23;
+impl Solver for Day23 {
+    fn day(&self) -> u8 {
+        23
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use anyhow::Result;
     use indoc::indoc;
 
-    fn input() -> Vec<String> {
-        let text = indoc! {"
+    fn input() -> Input {
+        Input::new(indoc! {"
             kh-tc
             qp-kh
             de-cg
@@ -143,19 +160,18 @@ mod test {
             wh-qp
             tb-vc
             td-yn
-        "};
-        text.lines().map(|x| x.to_string()).collect()
+        "})
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(input())?, "7");
+        assert_eq!(part1(&input())?, "7");
         Ok(())
     }
 
     #[test]
     fn test_part2() -> Result<()> {
-        assert_eq!(part2(input())?, "co,de,ka,ta");
+        assert_eq!(part2(&input())?, "co,de,ka,ta");
         Ok(())
     }
 }
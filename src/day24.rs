@@ -1,41 +1,46 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
-use std::fmt::Display;
-use std::{cell::RefCell, collections::BTreeSet};
+use std::fmt::{Display, Write};
+use std::fs;
+use std::str::FromStr;
 
 use anyhow::{anyhow, bail, Result};
 use itertools::Itertools;
+use log::info;
 use regex::Regex;
 
-// OK look...
-// This is gross.
-// This is ugly.
-// But writing the code and gradually getting to the point where each circuit is
-// labelled allowed me to filter out the outputs that looked "fine" (as in
-// `q[n]^c[n-1]`)
-//
-// To make this "for real" probably just take the labelling approach all the way
-// to its extreme. Eliminate all z-outputs that have "good" `q[n]^c[n-1]` shape,
-// then brute-force the remainder, including possible x[n] <-> y[n] swaps (that
-// was the final one for me.)
-//
-// Full adder has 5 gates and looks like:
+use crate::common::{Input, Line};
+use crate::solver::{parsing, Param, Params, Solver};
+
+/// The puzzle swaps exactly this many pairs of outputs
+const SWAPPED_PAIRS: usize = 4;
+
+/// Where the adder first stops looking like one, and swaps that might fix it
+struct Fault<'a> {
+    bit: usize,
+    swaps: Vec<(&'a str, &'a str)>,
+}
+
+// Part 2's circuit is a ripple-carry adder with some gate outputs swapped.
+// Each full adder has 5 gates:
 //     q[n] = x[n] ^ y[n]    -- intermediary "q", partial sum
 //     r[n] = x[n] & y[n]    -- intermediary "r", partial carry
 //     p[n] = q[n] & c[n-1]  -- intermediary "p", partial carry
 //     c[n] = p[n] | r[n]    -- c = full carry is "p OR r"
 //     z[n] = q[n] ^ c[n-1]  -- z = full sum (carry + x + y)
 //
-// Base-case is:
-//     r00 = x00 & y00
-//     q00 = x00 ^ y00
-//     p00 = q00 & 0 = 0
-//     c00 = r00 | 0 = r00
-//     z00 = q00 ^ 0 = q00
+// Base-case is a half adder:
+//     z00 = q00 = x00 ^ y00
+//     c00 = r00 = x00 & y00
 //
-// Terminal case is (for 44-bit adder):
+// Terminal case is (for 45-bit adder):
 //     z45 = c44
 //
-// Good luck.
+// q and r only depend on the inputs, so they can always be found. Walking up
+// from bit 0, each bit's gates are located from q, r and the carry in; the
+// first one that's missing or lands on the wrong output gives a few candidate
+// swaps. Each is tried in turn, and the one that gets the walk furthest is
+// kept; there's no backtracking past it.
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Operand {
@@ -52,6 +57,24 @@ impl Display for Operand {
         }
     }
 }
+impl Operand {
+    fn keyword(&self) -> &'static str {
+        match self {
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+        }
+    }
+
+    /// Graphviz node shape for gates of this kind
+    fn shape(&self) -> &'static str {
+        match self {
+            Self::And => "box",
+            Self::Or => "ellipse",
+            Self::Xor => "diamond",
+        }
+    }
+}
 
 #[derive(Clone)]
 struct Gate<'a> {
@@ -59,6 +82,8 @@ struct Gate<'a> {
     op: Operand,
     rhs: &'a str,
     collapse: Option<String>,
+    /// Where the gate was defined in the input, 1-based
+    line: usize,
 }
 impl Display for Gate<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -76,32 +101,43 @@ struct Chal<'a> {
     z_names: Vec<&'a str>,
 }
 impl<'a> Chal<'a> {
-    fn parse(lines: &'a Vec<String>) -> Result<Self> {
+    fn parse(sections: &'a [Input; 2]) -> Result<Self> {
+        let [wires, gate_lines] = sections;
         let mut values: HashMap<&str, usize> = HashMap::new();
-        let mut iter = lines.iter();
-        while let Some(line) = iter.next() {
-            if line == "" {
-                break;
+        for line in wires.numbered_lines() {
+            let (name, value) = line.split_once(": ", "`wire: value`")?;
+            if !name.starts_with(['x', 'y']) {
+                let msg = format!("input wire {} should be xNN or yNN", name);
+                return Err(line.error_in(name, msg).into());
             }
-            let (name, value) = line.split_once(": ").unwrap();
-            values.insert(name, value.parse()?);
+            bit_number(name).map_err(|e| line.error_in(name, e))?;
+            let value = match value {
+                "0" => 0,
+                "1" => 1,
+                _ => {
+                    let msg = format!("{} should be 0 or 1", name);
+                    return Err(line.error_in(value, msg).into());
+                }
+            };
+            values.insert(name, value);
         }
 
         let mut gates: HashMap<&str, Gate> = HashMap::new();
         let mut z_names = Vec::new();
         let gate_re: Regex =
             Regex::new(r"^([a-z0-9]+) (AND|X?OR) ([a-z0-9]+) -> ([a-z0-9]+)$").unwrap();
-        while let Some(line) = iter.next() {
+        let lines: Vec<Line> = gate_lines.numbered_lines().collect();
+        for line in &lines {
             let cap = gate_re
-                .captures(line)
-                .ok_or_else(|| anyhow!("didn't match regex"))?;
+                .captures(line.text)
+                .ok_or_else(|| line.error("expected `wire AND|OR|XOR wire -> wire`"))?;
 
             let a = cap.get(1).unwrap().as_str();
             let op = match &cap[2] {
                 "AND" => Operand::And,
                 "OR" => Operand::Or,
                 "XOR" => Operand::Xor,
-                e => bail!("invalid operand {}", e),
+                e => return Err(line.error_in(e, "invalid operand").into()),
             };
             let b = cap.get(3).unwrap().as_str();
 
@@ -111,13 +147,43 @@ impl<'a> Chal<'a> {
                 op,
                 rhs,
                 collapse: None,
+                line: line.index + 1,
             };
             let gate_name = cap.get(4).unwrap().as_str();
+            if values.contains_key(gate_name) {
+                let msg = format!("{} is an input and can't be driven", gate_name);
+                return Err(line.error_in(gate_name, msg).into());
+            }
+            if let Some(other) = gates.get(gate_name) {
+                let msg = format!("{} is already driven by line {}", gate_name, other.line);
+                return Err(line.error_in(gate_name, msg).into());
+            }
             if gate_name.starts_with('z') {
+                bit_number(gate_name).map_err(|e| line.error_in(gate_name, e))?;
                 z_names.push(gate_name);
             }
             gates.insert(gate_name, gate);
         }
+
+        let mut undefined: Vec<(usize, &str)> = gates
+            .values()
+            .flat_map(|g| [(g.line, g.lhs), (g.line, g.rhs)])
+            .filter(|(_, name)| !values.contains_key(name) && !gates.contains_key(name))
+            .collect();
+        if !undefined.is_empty() {
+            undefined.sort();
+            undefined.dedup();
+            let msgs: Vec<String> = undefined
+                .iter()
+                .map(|&(line, name)| {
+                    let line = lines.iter().find(|l| l.index + 1 == line).unwrap();
+                    line.error_in(name, format!("undefined wire {}", name))
+                        .to_string()
+                })
+                .collect();
+            bail!("{}", msgs.join("\n"));
+        }
+
         z_names.sort();
         Ok(Self {
             values: RefCell::new(values),
@@ -126,55 +192,66 @@ impl<'a> Chal<'a> {
         })
     }
 
-    fn resolve(&self, name: &'a str) -> usize {
+    fn resolve(&self, name: &'a str) -> Result<usize> {
+        self.resolve_from(name, &mut vec![])
+    }
+
+    /// `path` holds the gates being resolved further up, to spot loops
+    fn resolve_from(&self, name: &'a str, path: &mut Vec<&'a str>) -> Result<usize> {
         if let Some(value) = self.values.borrow().get(name) {
-            return *value;
+            return Ok(*value);
         }
-        let g = self.gates.get(name).unwrap();
-        let lhs = self.resolve(g.lhs);
-        let rhs = self.resolve(g.rhs);
+        let Some(g) = self.gates.get(name) else {
+            bail!("undefined wire {}", name);
+        };
+        if let Some(start) = path.iter().position(|n| *n == name) {
+            let cycle = path[start..].iter().chain([&name]).join(" -> ");
+            bail!("combinational loop: {}", cycle);
+        }
+        path.push(name);
+        let lhs = self.resolve_from(g.lhs, path)?;
+        let rhs = self.resolve_from(g.rhs, path)?;
+        path.pop();
         let value = match g.op {
             Operand::And => lhs & rhs,
             Operand::Or => lhs | rhs,
             Operand::Xor => lhs ^ rhs,
         };
         self.values.borrow_mut().insert(name, value);
-        value
+        Ok(value)
     }
 
+    /// The expression driving `name`, down to inputs or labelled gates
     fn trace(&self, name: &'a str) -> String {
-        let g = self.gates.get(name).unwrap();
+        self.trace_from(name, &mut vec![])
+    }
+
+    fn trace_from(&self, name: &'a str, path: &mut Vec<&'a str>) -> String {
+        let Some(g) = self.gates.get(name) else {
+            return name.to_owned();
+        };
         if let Some(label) = &g.collapse {
             return label.clone();
         }
+        if path.contains(&name) {
+            return format!("{name}(...)");
+        }
 
-        let lhs = if g.lhs.starts_with('x') || g.lhs.starts_with('y') {
-            g.lhs.to_owned()
-        } else {
-            self.trace(g.lhs)
-        };
-        let rhs = if g.rhs.starts_with('x') || g.rhs.starts_with('y') {
-            g.rhs.to_owned()
-        } else {
-            self.trace(g.rhs)
-        };
-        let infix = match g.op {
-            Operand::And => "&",
-            Operand::Or => "|",
-            Operand::Xor => "^",
-        };
-        let result: String = format!("{name}({lhs}{infix}{rhs})");
-        result
+        path.push(name);
+        let lhs = self.trace_from(g.lhs, path);
+        let rhs = self.trace_from(g.rhs, path);
+        path.pop();
+        format!("{name}({lhs}{}{rhs})", g.op)
     }
 
-    fn resolve_all(&self) -> usize {
+    fn resolve_all(&self) -> Result<usize> {
         let mut total: usize = 0;
         for name in self.z_names.iter() {
-            let z = &name[1..].parse::<i32>().expect("valid number suffix");
-            let value = self.resolve(name);
+            let z = bit_number(name)?;
+            let value = self.resolve(name)?;
             total |= value << z;
         }
-        total
+        Ok(total)
     }
 
     fn add_up_prefix(&self, prefix: char) -> usize {
@@ -183,7 +260,7 @@ impl<'a> Chal<'a> {
             if !name.starts_with(prefix) {
                 continue;
             }
-            let shift = &name[1..].parse::<i32>().expect("valid number");
+            let shift = bit_number(name).expect("checked while parsing");
             total |= value << shift;
         }
         total
@@ -192,280 +269,463 @@ impl<'a> Chal<'a> {
     fn set_label(&mut self, name: &'a str, label: String) {
         self.gates.get_mut(name).unwrap().collapse = Some(label);
     }
-}
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let c = Chal::parse(&lines)?;
-    let total = c.resolve_all();
-    Ok(total.to_string())
-}
+    /// Number of bits in each of x and y, which must leave room for the
+    /// carry out in a `usize`
+    fn width(&self) -> Result<usize> {
+        let width = self
+            .values
+            .borrow()
+            .keys()
+            .filter(|name| name.starts_with('x'))
+            .count();
+        if width >= usize::BITS as usize {
+            bail!("{}-bit inputs are too wide to add", width);
+        }
+        Ok(width)
+    }
+
+    /// Output of the gate `a op b`
+    fn find(&self, a: &str, op: Operand, b: &str) -> Option<&'a str> {
+        let (lhs, rhs) = if a < b { (a, b) } else { (b, a) };
+        // Lowest name if the same gate appears twice, to be repeatable
+        self.gates
+            .iter()
+            .filter(|(_, g)| g.op == op && g.lhs == lhs && g.rhs == rhs)
+            .map(|(name, _)| *name)
+            .min()
+    }
+
+    fn swap(&mut self, a: &'a str, b: &'a str) {
+        let ga = self.gates.remove(a).unwrap();
+        let gb = self.gates.remove(b).unwrap();
+        self.gates.insert(a, gb);
+        self.gates.insert(b, ga);
+    }
 
-pub fn part2(lines: Vec<String>) -> Result<String> {
-    let mut c = Chal::parse(&lines)?;
-    let mut problems: BTreeSet<&str> = BTreeSet::new();
-
-    let x = c.add_up_prefix('x');
-    let y = c.add_up_prefix('y');
-    let expect_z = x + y;
-    println!("x : {x:045b} + \ny : {y:045b} = \nz: {expect_z:045b}");
-
-    let mut p_n = vec!["___"; 45];
-    let mut q_n = vec!["___"; 45];
-    let mut r_n = vec!["___"; 45];
-    let mut c_n = vec!["___"; 45];
-    {
-        // Check z00 is correct for adding
-        let z00 = c.gates.get("z00").unwrap();
-        assert!(z00.op == Operand::Xor);
-        assert!(z00.lhs == "x00");
-        assert!(z00.rhs == "y00");
-        q_n[0] = "z00";
-    }
-
-    {
-        // Locate c00 and r00. Gate p00 is never set
-        let mut r00: Option<&str> = None;
-        for (name, gate) in &c.gates {
-            match (gate.op, gate.lhs, gate.rhs) {
-                (Operand::And, "x00", "y00") => {
-                    if r00.is_some() {
-                        bail!("duplicate gate for r00")
+    /// Walks the adder from bit 0, labelling q/r/p/c signals as it goes.
+    /// Returns the first fault, or `None` if it's a well-formed adder.
+    fn check(&mut self, width: usize) -> Result<Option<Fault<'a>>> {
+        for gate in self.gates.values_mut() {
+            gate.collapse = None;
+        }
+        if self.z_names.len() != width + 1 {
+            bail!(
+                "expected {} z outputs for a {}-bit adder, found {}",
+                width + 1,
+                width,
+                self.z_names.len()
+            );
+        }
+        let mut carry: Option<&'a str> = None;
+        for bit in 0..width {
+            let x = format!("x{:02}", bit);
+            let y = format!("y{:02}", bit);
+            let z = self.z_names[bit];
+            let q = self
+                .find(&x, Operand::Xor, &y)
+                .ok_or_else(|| anyhow!("no {} XOR {} gate", x, y))?;
+            let r = self
+                .find(&x, Operand::And, &y)
+                .ok_or_else(|| anyhow!("no {} AND {} gate", x, y))?;
+
+            let Some(c_in) = carry else {
+                // Half adder
+                if q != z {
+                    return Ok(Some(Fault {
+                        bit,
+                        swaps: vec![(q, z)],
+                    }));
+                }
+                self.set_label(q, "q00".to_owned());
+                self.set_label(r, "c00".to_owned());
+                carry = Some(r);
+                continue;
+            };
+
+            match self.find(q, Operand::Xor, c_in) {
+                Some(sum) if sum != z => {
+                    return Ok(Some(Fault {
+                        bit,
+                        swaps: vec![(sum, z)],
+                    }))
+                }
+                Some(_) => {}
+                None => {
+                    // One of z's inputs is right and the other one got swapped
+                    let g = &self.gates[z];
+                    if g.op != Operand::Xor {
+                        bail!("can't repair bit {}: {} = {}", bit, z, self.trace(z));
                     }
-                    r00 = Some(name)
+                    let mut swaps = vec![];
+                    for (ours, theirs) in [(g.lhs, g.rhs), (g.rhs, g.lhs)] {
+                        if ours == q {
+                            swaps.push((theirs, c_in));
+                        } else if ours == c_in {
+                            swaps.push((theirs, q));
+                        }
+                    }
+                    return Ok(Some(Fault { bit, swaps }));
                 }
-                _ => {}
             }
+
+            let Some(p) = self.find(q, Operand::And, c_in) else {
+                bail!("can't repair bit {}: no {} AND {} gate", bit, q, c_in);
+            };
+            let Some(c_out) = self.find(p, Operand::Or, r) else {
+                // Any OR reading p or r might be the carry, with the other
+                // side swapped
+                let mut swaps = vec![];
+                for g in self.gates.values().filter(|g| g.op == Operand::Or) {
+                    for (ours, theirs) in [(g.lhs, g.rhs), (g.rhs, g.lhs)] {
+                        if ours == p {
+                            swaps.push((theirs, r));
+                        } else if ours == r {
+                            swaps.push((theirs, p));
+                        }
+                    }
+                }
+                return Ok(Some(Fault { bit, swaps }));
+            };
+            self.set_label(q, format!("q{:02}", bit));
+            self.set_label(r, format!("r{:02}", bit));
+            self.set_label(p, format!("p{:02}", bit));
+            self.set_label(c_out, format!("c{:02}", bit));
+            carry = Some(c_out);
         }
-        if let Some(name) = r00 {
-            println!("r00/c00 is {name}");
-            c_n[0] = name;
-            r_n[0] = name;
-            c.set_label(name, "r00/c00".to_owned());
-            // p[0] is never used and not set
-        } else {
-            bail!("r00 not found")
+
+        let z = self.z_names[width];
+        match carry {
+            Some(c_out) if c_out != z => Ok(Some(Fault {
+                bit: width,
+                swaps: vec![(c_out, z)],
+            })),
+            _ => Ok(None),
         }
     }
 
-    {
-        // confirm z01 is correct for adding
-        let z01 = c.gates.get("z01").unwrap();
-        assert!(z01.op == Operand::Xor);
-        let lhs = c.gates.get(z01.lhs).unwrap();
-        let rhs = c.gates.get(z01.rhs).unwrap();
-        let (c_name, _c, q_name, q) = match (&lhs.op, &rhs.op) {
-            (Operand::And, Operand::Xor) => (z01.lhs, lhs, z01.rhs, rhs),
-            (Operand::Xor, Operand::And) => (z01.rhs, rhs, z01.lhs, lhs),
-            _ => bail!("z01 invalid for adder"),
-        };
-        println!("z01 = c00 {} ^ q01 {}", c_name, q_name);
-        assert_eq!(c_name, r_n[0]);
-        assert_eq!(c_name, c_n[0]);
-        assert!(q.lhs == "x01");
-        assert!(q.rhs == "y01");
-        q_n[1] = q_name;
-        c.set_label(c_name, "c00".to_owned());
-        c.set_label(q_name, "q01".to_owned());
-    }
-
-    // confirm rest of base layer is correct
-    for (name, gate) in &c.gates {
-        // gate names get sorted, so x will always be lhs
-        if gate.lhs.starts_with('x') {
-            if gate.rhs.starts_with('x') {
-                bail!("base layer x.x conflict: {}: {}", name, c.trace(name))
-            } else if gate.rhs.starts_with('y') {
-                let x = &gate.lhs[1..].parse::<usize>().expect("valid number suffix");
-                let y = &gate.rhs[1..].parse::<usize>().expect("valid number suffix");
-                if x != y {
-                    bail!("base layer conflict: {}: {}", name, c.trace(name))
+    /// Swaps outputs until the adder is well-formed, returning the pairs
+    /// swapped. At each fault, every candidate swap is tried and the one
+    /// that gets the walk furthest is kept, the first in name order on a tie.
+    fn repair(&mut self, width: usize) -> Result<Vec<(&'a str, &'a str)>> {
+        let mut swapped = vec![];
+        while let Some(fault) = self.check(width)? {
+            if swapped.len() == SWAPPED_PAIRS {
+                bail!(
+                    "bit {} is still broken after swapping {}",
+                    fault.bit,
+                    swapped
+                        .iter()
+                        .map(|(a, b)| format!("{}/{}", a, b))
+                        .join(",")
+                );
+            }
+            // Candidates come in no particular order, so sort them to get
+            // the same repair every run
+            let mut candidates: Vec<_> = fault
+                .swaps
+                .into_iter()
+                .filter(|(a, b)| a != b && self.gates.contains_key(a) && self.gates.contains_key(b))
+                .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+                .collect();
+            candidates.sort();
+            candidates.dedup();
+
+            let mut best: Option<(usize, (&'a str, &'a str))> = None;
+            for (a, b) in candidates {
+                self.swap(a, b);
+                // How far the walk gets now. A swap can also close a loop
+                // without breaking the shape so far.
+                let reached = match self.check(width) {
+                    Ok(None) => Some(usize::MAX),
+                    Ok(Some(f)) => Some(f.bit),
+                    Err(_) => None,
                 }
-                match gate.op {
-                    Operand::Or => bail!("base layer OR: {}: {}", name, c.trace(name)),
-                    Operand::Xor => q_n[*x] = name,
-                    Operand::And => r_n[*x] = name,
+                .filter(|_| self.add(0, 0).is_ok());
+                self.swap(a, b);
+                if let Some(reached) = reached {
+                    if reached > fault.bit && best.is_none_or(|(furthest, _)| reached > furthest) {
+                        best = Some((reached, (a, b)));
+                    }
                 }
-                if gate.op == Operand::Or {}
             }
-        } else if gate.lhs.starts_with('y') {
-            bail!("base layer y on lhs: {}: {}", name, c.trace(name))
+            let Some((_, (a, b))) = best else {
+                bail!("no single swap repairs bit {}", fault.bit);
+            };
+            self.swap(a, b);
+            swapped.push((a, b));
         }
+        Ok(swapped)
     }
 
-    println!("q base layer: {}", q_n.iter().join(","));
-    for (n, name) in q_n.iter().enumerate().skip(1) {
-        // skip z00, which is always in sum_n[0]
-        if name.starts_with('z') {
-            println!(
-                "base layer q-signal should not be output layer: {} needs swap",
-                name
-            );
-            problems.insert(name);
-        } else {
-            c.set_label(name, format!("q{:02}", n));
+    /// Runs `x + y` through the circuit
+    fn add(&self, x: usize, y: usize) -> Result<usize> {
+        {
+            let mut values = self.values.borrow_mut();
+            values.retain(|name, _| !self.gates.contains_key(name));
+            for (name, value) in values.iter_mut() {
+                let bit = bit_number(name)?;
+                let n = if name.starts_with('x') { x } else { y };
+                *value = (n >> bit) & 1;
+            }
         }
+        self.resolve_all()
     }
-    println!("r base layer: {}", r_n.iter().join(","));
-    for (n, name) in r_n.iter().enumerate() {
-        if name.starts_with('z') {
-            println!(
-                "base layer r-signal should not be output layer: {} needs swap",
-                name
-            );
-            problems.insert(name);
-        } else {
-            c.set_label(name, format!("r{:02}", n));
-        }
-    }
-
-    // AND gates are _either_ in the r-signal set (i.e., x[n]&y[n]) _or_ p-signal set (i.e., p[n] = q[n]&c[n-1])
-    // Find AND gates that aren't in r:
-    for (name, gate) in c
-        .gates
-        .iter()
-        .filter(|(_name, gate)| gate.op == Operand::And)
-        .filter(|(name, _gate)| !r_n.contains(*name))
-    {
-        let lhs = c.gates.get(gate.lhs).unwrap();
-        let rhs = c.gates.get(gate.rhs).unwrap();
-        let n_lhs = q_n.iter().position(|name| *name == gate.lhs);
-        let n_rhs = q_n.iter().position(|name| *name == gate.rhs);
-        if let Some(n) = n_lhs {
-            // rhs should look like c[n-1]
-            if rhs.op == Operand::Or {
-                // fine, maybe add to p[n]?
-            } else if gate.rhs == c_n[0] {
-                // base-case, likely this is c[1]
-                assert_eq!(q_n[1], gate.lhs);
-            } else {
-                println!(
-                    "rhs of AND doesn't look like c (should be OR): n={} {}{} -> {}{} & {}{}",
-                    n, name, gate, gate.lhs, lhs, gate.rhs, rhs
-                );
-                problems.insert(gate.rhs);
+
+    /// Input wires, i.e. the ones no gate drives
+    fn inputs(&self) -> Vec<&'a str> {
+        let values = self.values.borrow();
+        let mut inputs: Vec<_> = values
+            .keys()
+            .copied()
+            .filter(|name| !self.gates.contains_key(name))
+            .collect();
+        inputs.sort();
+        inputs
+    }
+
+    fn sorted_gates(&self) -> Vec<(&'a str, &Gate<'a>)> {
+        let mut gates: Vec<_> = self.gates.iter().map(|(n, g)| (*n, g)).collect();
+        gates.sort_by_key(|(name, _)| *name);
+        gates
+    }
+
+    /// Graphviz digraph of the circuit. Labelled gates are filled and
+    /// `problems` are outlined in red.
+    fn to_dot(&self, problems: &[&str]) -> String {
+        let mut out = String::new();
+        _ = writeln!(out, "digraph day24 {{");
+        _ = writeln!(out, "    rankdir=LR;");
+        _ = writeln!(out, "    node [fontname=\"monospace\"];");
+        for name in self.inputs() {
+            _ = writeln!(out, "    \"{}\" [shape=invtriangle];", name);
+        }
+        for (name, gate) in self.sorted_gates() {
+            let mut attrs = vec![format!("shape={}", gate.op.shape())];
+            let mut label = format!("{}\\n{}", name, gate.op.keyword());
+            if let Some(l) = &gate.collapse {
+                label += &format!("\\n{}", l);
+                attrs.push("style=filled".to_owned());
+                attrs.push("fillcolor=lightblue".to_owned());
             }
-        } else if let Some(n) = n_rhs {
-            // lhs should look like c[n-1]
-            if lhs.op == Operand::Or {
-                // fine, maybe add to p[n]?
-            } else if gate.lhs == c_n[0] {
-                // base-case, likely this is c[1]
-                assert_eq!(q_n[1], gate.rhs);
-            } else {
-                println!(
-                    "lhs of AND doesn't look like c (should be OR): n={} {}{} -> {}{} & {}{}",
-                    n, name, gate, gate.lhs, lhs, gate.rhs, rhs
-                );
-                problems.insert(gate.lhs);
+            attrs.insert(0, format!("label=\"{}\"", label));
+            if self.z_names.contains(&name) {
+                attrs.push("peripheries=2".to_owned());
+            }
+            if problems.contains(&name) {
+                attrs.push("color=red".to_owned());
+                attrs.push("penwidth=3".to_owned());
+            }
+            _ = writeln!(out, "    \"{}\" [{}];", name, attrs.join(", "));
+        }
+        for (name, gate) in self.sorted_gates() {
+            for input in [gate.lhs, gate.rhs] {
+                _ = writeln!(out, "    \"{}\" -> \"{}\";", input, name);
             }
         }
+        _ = writeln!(out, "}}");
+        out
     }
 
-    // OR gates are used exclusively for the carry-out (c_n) bit
-    let mut label_after: Vec<(&str, String)> = vec![];
-    {
-        for (c_name, c_gate) in c
-            .gates
-            .iter()
-            .filter(|(_name, gate)| gate.op == Operand::Or)
-        {
-            // c[n] = p[n] | r[n]  -- we don't know p[n] yet
-            let maybe_r_lhs = r_n.iter().position(|name| *name == c_gate.lhs);
-            let maybe_r_rhs = r_n.iter().position(|name| *name == c_gate.rhs);
-            match (maybe_r_lhs, maybe_r_rhs) {
-                (Some(_n_l), Some(_n_r)) => {
-                    // TODO: something? Doesn't happen for my input
-                    bail!("do something with two r-signals in OR")
-                }
-                (None, None) => {
-                    let lhs = c.gates.get(c_gate.lhs).unwrap();
-                    let rhs = c.gates.get(c_gate.rhs).unwrap();
-                    println!(
-                        "degenerate OR gate: neither side is r: {} {} -> {}={} {}={}",
-                        c_name, c_gate, c_gate.lhs, lhs, c_gate.rhs, rhs
-                    );
-                    // TODO: delve into sides
-                }
-                (None, Some(n)) => {
-                    // well-formed, right is r-signal
-                    let lhs = c.gates.get(c_gate.lhs).unwrap();
-                    c_n[n] = c_name;
-                    if lhs.op == Operand::And {
-                        p_n[n] = c_gate.lhs; // found p-signal
-                        label_after.push((c_name, format!("c{:02}", n)));
-                        label_after.push((&c_gate.lhs, format!("p{:02}", n)));
-                    } else {
-                        println!(
-                            "p-side of OR gate is not AND: {} {} n={}",
-                            c_gate.lhs, lhs, n
-                        );
-                        problems.insert(c_gate.lhs);
-                    }
-                }
-                (Some(n), None) => {
-                    // well-formed, left is r-signal
-                    let rhs = c.gates.get(c_gate.rhs).unwrap();
-                    // check rhs for p-signal-ness
-                    c_n[n] = c_name;
-                    if rhs.op == Operand::And {
-                        p_n[n] = c_gate.rhs; // found p-signal
-                        label_after.push((c_name, format!("c{:02}", n)));
-                        label_after.push((&c_gate.rhs, format!("p{:02}", n)));
-                    } else {
-                        println!(
-                            "p-side of OR gate is not AND: {} {}, n={}",
-                            c_gate.rhs, rhs, n
-                        );
-                        problems.insert(c_gate.rhs);
-                    }
-                }
+    /// Structural Verilog netlist with x, y and z as buses. Labels and
+    /// `problems` are noted in comments.
+    fn to_verilog(&self, problems: &[&str]) -> String {
+        let inputs = self.inputs();
+        let bus_width = |prefix: char| inputs.iter().filter(|n| n.starts_with(prefix)).count();
+        let mut out = String::new();
+        _ = writeln!(out, "module day24(");
+        _ = writeln!(out, "    input [{}:0] x,", bus_width('x').saturating_sub(1));
+        _ = writeln!(out, "    input [{}:0] y,", bus_width('y').saturating_sub(1));
+        _ = writeln!(
+            out,
+            "    output [{}:0] z",
+            self.z_names.len().saturating_sub(1)
+        );
+        _ = writeln!(out, ");");
+        let gates = self.sorted_gates();
+        for (name, _) in gates.iter().filter(|(n, _)| !self.z_names.contains(n)) {
+            _ = writeln!(out, "    wire {};", verilog_wire(name));
+        }
+        for (name, gate) in gates {
+            let mut notes: Vec<&str> = gate.collapse.iter().map(|l| l.as_str()).collect();
+            if problems.contains(&name) {
+                notes.push("swapped");
+            }
+            _ = write!(
+                out,
+                "    assign {} = {} {} {};",
+                verilog_wire(name),
+                verilog_wire(gate.lhs),
+                gate.op,
+                verilog_wire(gate.rhs)
+            );
+            if !notes.is_empty() {
+                _ = write!(out, " // {}", notes.join(", "));
             }
+            _ = writeln!(out);
         }
+        _ = writeln!(out, "endmodule");
+        out
     }
-    for (name, label) in label_after {
-        c.set_label(name, label);
+}
+
+/// Verilog keywords a three-letter wire name could collide with
+const VERILOG_KEYWORDS: [&str; 14] = [
+    "and", "bit", "buf", "end", "for", "int", "let", "nor", "not", "or", "reg", "tri", "wor", "xor",
+];
+
+/// `x05` as `x[5]`, escaping names that are keywords
+fn verilog_wire(name: &str) -> String {
+    let (prefix, n) = name.split_at(1);
+    match (prefix, n.parse::<usize>()) {
+        ("x" | "y" | "z", Ok(bit)) => format!("{}[{}]", prefix, bit),
+        _ if VERILOG_KEYWORDS.contains(&name) => format!("\\{} ", name),
+        _ => name.to_owned(),
     }
+}
+
+/// What `--export` writes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Export {
+    None,
+    Dot,
+    Verilog,
+}
+
+impl FromStr for Export {
+    type Err = anyhow::Error;
 
-    // check carry-signals are not output
-    println!("carry   : {}", c_n.iter().join(","));
-    for z_name in c_n.iter().filter(|name| name.starts_with('z')) {
-        if *z_name != "z45" {
-            println!("z-signal should never be carry: {}", z_name);
-            problems.insert(z_name);
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "dot" => Ok(Self::Dot),
+            "verilog" => Ok(Self::Verilog),
+            _ => Err(anyhow!("expected none, dot or verilog")),
         }
     }
+}
 
-    // check p-signals are not output (except p00, which doesn't exist)
-    println!("p-signal: {}", p_n.iter().join(","));
-    for z_name in p_n.iter().skip(1).filter(|name| name.starts_with('z')) {
-        println!("z-signal should never be p-signal: {}", z_name);
-        problems.insert(z_name);
+impl Export {
+    /// Writes the circuit to `path`, or `day24.dot`/`day24.v` if empty
+    fn write(self, c: &Chal, problems: &[&str], path: &str) -> Result<()> {
+        let (text, default_path) = match self {
+            Self::None => return Ok(()),
+            Self::Dot => (c.to_dot(problems), "day24.dot"),
+            Self::Verilog => (c.to_verilog(problems), "day24.v"),
+        };
+        let path = if path.is_empty() { default_path } else { path };
+        fs::write(path, text)?;
+        info!("wrote {}", path);
+        Ok(())
     }
+}
 
-    println!("resolutions:");
-    for (n, z_name) in c.z_names.iter().enumerate() {
-        // z45 should be c44
-        let trace = c.trace(z_name);
-        if n > 0 {
-            // filter-out fully-resolved gates:
-            let expect_one = format!("z{:02}(q{:02}^c{:02})", n, n, n - 1);
-            let expect_two = format!("z{:02}(c{:02}^q{:02})", n, n - 1, n);
-            if trace == expect_one || trace == expect_two {
-                continue;
-            }
+/// The bit a wire like `z05` stands for
+/// The bit `name` stands for, e.g. 5 for `x05`. Values are added up in a
+/// `usize`, so the bit has to fit in one.
+fn bit_number(name: &str) -> Result<usize> {
+    let bit: usize = name[1..]
+        .parse()
+        .map_err(|_| anyhow!("wire {} has no bit number", name))?;
+    if bit >= usize::BITS as usize {
+        bail!("wire {} is past the {} bits supported", name, usize::BITS);
+    }
+    Ok(bit)
+}
+
+/// Checks the circuit adds, using the puzzle's own x and y, the edge cases
+/// and a batch of pseudo-random pairs
+fn verify_adder(c: &Chal, width: usize, puzzle: (usize, usize)) -> Result<()> {
+    let mask = (1 << width) - 1;
+    let mut cases = vec![puzzle, (0, 0), (mask, 1), (mask, mask)];
+    // xorshift, so runs are repeatable
+    let mut state: u64 = 0x2024_1224;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize & mask
+    };
+    cases.extend((0..100).map(|_| (next(), next())));
+    for (x, y) in cases {
+        let z = c.add(x, y)?;
+        if z != x + y {
+            bail!("repaired circuit says {} + {} = {}", x, y, z);
         }
-        println!("{} = {}", z_name, trace);
     }
-    for (name, gate) in c.gates.iter() {
-        if let Some(label) = &gate.collapse {
-            // TODO: these are specific to my input and were some swapped q-&r-signals under a carry signal
-            if label == "r35" || label == "q35" {
-                println!("odd duck: {} is {}", name, gate);
-            }
+    Ok(())
+}
+
+fn part1(input: &Input, export: Export, export_to: &str) -> Result<String> {
+    let sections = parsing(|| input.expect_sections(["wires", "gates"]))?;
+    let c = parsing(|| Chal::parse(&sections))?;
+    export.write(&c, &[], export_to)?;
+    let total = c.resolve_all()?;
+    Ok(total.to_string())
+}
+
+/// With `export`, writes the circuit as given, labelled by what each gate
+/// turned out to compute and with the swapped wires flagged
+fn part2(input: &Input, export: Export, export_to: &str) -> Result<String> {
+    let sections = parsing(|| input.expect_sections(["wires", "gates"]))?;
+    let mut c = parsing(|| Chal::parse(&sections))?;
+    let width = c.width()?;
+    // Repairing runs sums through the circuit, overwriting x and y
+    let puzzle = (c.add_up_prefix('x'), c.add_up_prefix('y'));
+    let swaps = c.repair(width)?;
+    verify_adder(&c, width, puzzle)?;
+
+    let mut swapped: Vec<&str> = swaps.iter().flat_map(|&(a, b)| [a, b]).collect();
+    swapped.sort();
+    if export != Export::None {
+        // Labels stay with their gates, so swapping back shows e.g. a carry
+        // driving a z output
+        for &(a, b) in &swaps {
+            c.swap(a, b);
         }
+        export.write(&c, &swapped, export_to)?;
+    }
+    Ok(swapped.into_iter().join(","))
+}
+
+pub struct Day24;
+impl Solver for Day24 {
+    fn day(&self) -> u8 {
+        24
+    }
+
+    fn params(&self) -> &'static [Param] {
+        &[
+            Param {
+                key: "export",
+                default: "none",
+                help: "also write the circuit: none, dot or verilog",
+                check: Param::parses::<Export>,
+            },
+            Param {
+                key: "export_to",
+                default: "",
+                help: "file for the export, default day24.dot or day24.v",
+                check: Param::parses::<String>,
+            },
+        ]
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String> {
+        part1(
+            input,
+            params.get("export")?,
+            &params.get::<String>("export_to")?,
+        )
+    }
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String> {
+        part2(
+            input,
+            params.get("export")?,
+            &params.get::<String>("export_to")?,
+        )
     }
-    println!("problems: {}", problems.into_iter().join(","));
-    bail!("now go figure it out");
 }
 
 #[cfg(test)]
@@ -474,8 +734,8 @@ mod test {
     use anyhow::Result;
     use indoc::indoc;
 
-    fn to_lines() -> Vec<String> {
-        let text = indoc! {"
+    fn input() -> Input {
+        Input::new(indoc! {"
             x00: 1
             x01: 0
             x02: 1
@@ -523,21 +783,264 @@ mod test {
             hwm AND bqk -> z03
             tgd XOR rvg -> z12
             tnw OR pbm -> gnj
-        "};
-        text.lines().map(|x| x.to_string()).collect()
+        "})
     }
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(to_lines())?, "2024");
+        assert_eq!(part1(&input(), Export::None, "")?, "2024");
         Ok(())
     }
 
+    /// A `width`-bit ripple-carry adder computing 11 + 13 (mod 2^width),
+    /// with the named outputs swapped
+    fn adder(width: usize, swaps: &[(&str, &str)]) -> Input {
+        let mut lines = vec![];
+        for (prefix, n) in [('x', 11usize), ('y', 13)] {
+            for bit in 0..width {
+                lines.push(format!("{}{:02}: {}", prefix, bit, (n >> bit) & 1));
+            }
+        }
+        lines.push(String::new());
+        let mut gate = |a: String, op: &str, b: String, out: String| {
+            let out = swaps
+                .iter()
+                .find_map(|&(l, r)| match () {
+                    _ if out == l => Some(r.to_owned()),
+                    _ if out == r => Some(l.to_owned()),
+                    _ => None,
+                })
+                .unwrap_or(out);
+            lines.push(format!("{} {} {} -> {}", a, op, b, out));
+        };
+        let name = |prefix: char, bit: usize| format!("{}{:02}", prefix, bit);
+        gate(name('x', 0), "XOR", name('y', 0), name('z', 0));
+        gate(name('x', 0), "AND", name('y', 0), name('c', 0));
+        for bit in 1..width {
+            let carry = if bit == width - 1 {
+                name('z', width)
+            } else {
+                name('c', bit)
+            };
+            gate(name('x', bit), "XOR", name('y', bit), name('q', bit));
+            gate(name('y', bit), "AND", name('x', bit), name('r', bit));
+            gate(name('c', bit - 1), "XOR", name('q', bit), name('z', bit));
+            gate(name('q', bit), "AND", name('c', bit - 1), name('p', bit));
+            gate(name('r', bit), "OR", name('p', bit), carry);
+        }
+        Input::new(&lines.join("\n"))
+    }
+
     #[test]
     fn test_part2() -> Result<()> {
-        for (i, j) in vec![1, 2, 3, 4, 5, 6, 7, 8].iter().tuple_combinations() {
-            println!("{i}-{j}")
+        let swaps = [
+            ("z03", "c03"),
+            ("q06", "r06"),
+            ("p09", "z09"),
+            ("r10", "c10"),
+        ];
+        assert_eq!(
+            part2(&adder(12, &swaps), Export::None, "")?,
+            "c03,c10,p09,q06,r06,r10,z03,z09"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_other_widths() -> Result<()> {
+        assert_eq!(part2(&adder(5, &[]), Export::None, "")?, "");
+        assert_eq!(
+            part2(&adder(3, &[("z00", "c00")]), Export::None, "")?,
+            "c00,z00"
+        );
+        assert_eq!(
+            part2(&adder(4, &[("c02", "z04")]), Export::None, "")?,
+            "c02,z04"
+        );
+        let swaps = [
+            ("q44", "r44"),
+            ("z10", "p10"),
+            ("c20", "z20"),
+            ("r30", "c30"),
+        ];
+        assert_eq!(
+            part2(&adder(45, &swaps), Export::None, "")?,
+            "c20,c30,p10,q44,r30,r44,z10,z20"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_repair_repeatable() -> Result<()> {
+        // Carry candidates are gathered from a HashMap, which iterates in a
+        // different order each time one is built
+        let input = adder(12, &[("r06", "c06"), ("p09", "c09")]);
+        let sections = input.expect_sections(["wires", "gates"])?;
+        let first = Chal::parse(&sections)?.repair(12)?;
+        assert_eq!(first, vec![("c06", "r06"), ("c09", "p09")]);
+        for _ in 0..20 {
+            assert_eq!(Chal::parse(&sections)?.repair(12)?, first);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_export() -> Result<()> {
+        let input = adder(4, &[("z02", "c02")]);
+        let sections = input.expect_sections(["wires", "gates"])?;
+        let mut c = Chal::parse(&sections)?;
+        let swaps = c.repair(4)?;
+        assert_eq!(swaps, [("c02", "z02")]);
+        c.swap("c02", "z02");
+        let problems = ["c02", "z02"];
+
+        let dot = c.to_dot(&problems);
+        assert!(dot.starts_with("digraph day24 {\n"));
+        assert!(dot.contains("    \"x00\" [shape=invtriangle];\n"));
+        assert!(dot.contains(
+            "    \"r01\" [label=\"r01\\nAND\\nr01\", shape=box, style=filled, fillcolor=lightblue];\n"
+        ));
+        assert!(dot.contains(
+            "    \"z02\" [label=\"z02\\nOR\\nc02\", shape=ellipse, style=filled, \
+             fillcolor=lightblue, peripheries=2, color=red, penwidth=3];\n"
+        ));
+        assert!(dot.contains("    \"p02\" -> \"z02\";\n"));
+
+        let verilog = c.to_verilog(&problems);
+        assert!(verilog.starts_with(
+            "module day24(\n    input [3:0] x,\n    input [3:0] y,\n    output [4:0] z\n);\n"
+        ));
+        assert!(verilog.contains("    wire q01;\n"));
+        assert!(verilog.contains("    assign q01 = x[1] ^ y[1]; // q01\n"));
+        assert!(verilog.contains("    assign z[2] = p02 | r02; // c02, swapped\n"));
+        assert!(verilog.ends_with("endmodule\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_verilog_wire() {
+        assert_eq!(verilog_wire("x07"), "x[7]");
+        assert_eq!(verilog_wire("z45"), "z[45]");
+        assert_eq!(verilog_wire("xkq"), "xkq");
+        assert_eq!(verilog_wire("and"), "\\and ");
+    }
+
+    fn parse_err(text: &str) -> String {
+        let input = Input::new(text);
+        let sections = match input.expect_sections(["wires", "gates"]) {
+            Ok(sections) => sections,
+            Err(e) => return e.to_string(),
+        };
+        match Chal::parse(&sections) {
+            Ok(_) => panic!("parsed {:?}", text),
+            Err(e) => e.to_string(),
         }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_err("x00: 1\ny00: 0\n\nx00 AND y01 -> z00\nz00 OR qqq -> z01"),
+            indoc! {"
+                line 4, column 9: undefined wire y01
+                   4 | x00 AND y01 -> z00
+                     |         ^^^
+                line 5, column 8: undefined wire qqq
+                   5 | z00 OR qqq -> z01
+                     |        ^^^"}
+        );
+        assert_eq!(
+            parse_err("x00: 1\n\nx00 AND x00 -> z00\nx00 OR x00 -> z00"),
+            indoc! {"
+                line 4, column 15: z00 is already driven by line 3
+                   4 | x00 OR x00 -> z00
+                     |               ^^^"}
+        );
+        assert_eq!(
+            parse_err("x00: 2\n\nx00 AND x00 -> z00"),
+            indoc! {"
+                line 1, column 6: x00 should be 0 or 1
+                   1 | x00: 2
+                     |      ^"}
+        );
+        assert_eq!(
+            parse_err("x00: 1\n\nx00 NAND x00 -> z00"),
+            indoc! {"
+                line 3: expected `wire AND|OR|XOR wire -> wire`
+                   3 | x00 NAND x00 -> z00
+                     | ^^^^^^^^^^^^^^^^^^^"}
+        );
+        assert_eq!(
+            parse_err("x00: 1\ny00: 0"),
+            "line 3: expected gates after a blank line"
+        );
+        assert!(parse_err("x00: 1\n\nx00 AND x00 -> zed")
+            .starts_with("line 3, column 16: wire zed has no bit number"));
+        assert_eq!(
+            parse_err("x00: 1\ny00: 0\n\nx00 AND y00 -> z64"),
+            indoc! {"
+                line 4, column 16: wire z64 is past the 64 bits supported
+                   4 | x00 AND y00 -> z64
+                     |                ^^^"}
+        );
+        assert_eq!(
+            parse_err("x70: 1\n\nx70 AND x70 -> z00"),
+            indoc! {"
+                line 1, column 1: wire x70 is past the 64 bits supported
+                   1 | x70: 1
+                     | ^^^"}
+        );
+    }
+
+    #[test]
+    fn test_loop() -> Result<()> {
+        let input = Input::new(indoc! {"
+            x00: 1
+            y00: 0
+
+            x00 AND aaa -> bbb
+            bbb OR y00 -> aaa
+            aaa XOR x00 -> z00
+        "});
+        let e = part1(&input, Export::None, "").unwrap_err();
+        assert_eq!(e.to_string(), "combinational loop: aaa -> bbb -> aaa");
+
+        // Swapping a partial sum with a later carry feeds it back into itself
+        let input = adder(4, &[]);
+        let sections = input.expect_sections(["wires", "gates"])?;
+        let mut c = Chal::parse(&sections)?;
+        c.swap("q01", "c02");
+        assert!(c.add(1, 1).is_err());
+        c.swap("q01", "c02");
+        assert_eq!(c.add(5, 6)?, 11);
         Ok(())
     }
+
+    #[test]
+    fn test_part2_operands() -> Result<()> {
+        // Repairing overwrites x and y, so the puzzle's own sum has to be
+        // read beforehand to be verified
+        let input = adder(4, &[("z02", "c02")]);
+        let sections = input.expect_sections(["wires", "gates"])?;
+        let mut c = Chal::parse(&sections)?;
+        assert_eq!((c.add_up_prefix('x'), c.add_up_prefix('y')), (11, 13));
+        c.repair(4)?;
+        assert_ne!((c.add_up_prefix('x'), c.add_up_prefix('y')), (11, 13));
+
+        // The carry out of a 64-bit adder has nowhere to go
+        let e = part2(&adder(64, &[]), Export::None, "").unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("wire z64 is past the 64 bits supported"));
+        let mut wide = adder(64, &[]).text().to_owned();
+        wide.truncate(wide.rfind('\n').unwrap());
+        let e = part2(&Input::new(&wide), Export::None, "").unwrap_err();
+        assert_eq!(e.to_string(), "64-bit inputs are too wide to add");
+        Ok(())
+    }
+
+    #[test]
+    fn test_part2_not_an_adder() {
+        assert!(part2(&input(), Export::None, "").is_err());
+    }
 }
@@ -1,12 +1,17 @@
 use anyhow::{bail, Result};
 
+use crate::common::{Input, Line};
+use crate::solver::{parsing, Params, Solver, Unsolved};
+
 pub struct LockOrKey {
     pins: Vec<usize>,
 }
-impl LockOrKey {
-    pub fn default() -> Self {
+impl Default for LockOrKey {
+    fn default() -> Self {
         Self { pins: vec![0; 5] }
     }
+}
+impl LockOrKey {
     pub fn fits(&self, other: &LockOrKey) -> bool {
         for i in 0..5 {
             if self.pins[i] + other.pins[i] > 5 {
@@ -24,46 +29,50 @@ pub struct Chal {
 }
 
 impl Chal {
-    pub fn parse(lines: Vec<String>) -> Result<Chal> {
+    pub fn parse(input: &Input) -> Result<Chal> {
         let mut locks = vec![];
         let mut keys = vec![];
-        let mut iter = lines.into_iter();
-        loop {
+        for section in input.sections() {
+            let rows: Vec<Line> = section.numbered_lines().collect();
+            if rows.len() != 7 {
+                let msg = format!("expected 7 rows per lock or key, got {}", rows.len());
+                return Err(rows[0].error(msg).into());
+            }
             let mut cur = LockOrKey::default();
-            let is_lock = if let Some(type_line) = iter.next() {
-                type_line == "#####"
-            } else {
-                false
-            };
-
-            for _ in 0..5 {
-                let line = iter.next().unwrap();
-                for (i, c) in line.chars().enumerate() {
-                    match c {
-                        '.' => cur.pins[i] += 0,
-                        '#' => cur.pins[i] += 1,
-                        _ => bail!("invalid pin char"),
+            for line in &rows {
+                if line.text.chars().count() != cur.pins.len() {
+                    return Err(line
+                        .error(format!("expected {} columns", cur.pins.len()))
+                        .into());
+                }
+                for (i, c) in line.text.chars().enumerate() {
+                    if c != '.' && c != '#' {
+                        return Err(line.error_at(i, "invalid pin char").into());
+                    }
+                }
+            }
+            // top and bottom rows only say which kind it is
+            for line in &rows[1..6] {
+                for (i, c) in line.text.chars().enumerate() {
+                    if c == '#' {
+                        cur.pins[i] += 1;
                     }
                 }
             }
-            iter.next(); // ignore bottom line
 
-            if is_lock {
+            if rows[0].text == "#####" {
                 locks.push(cur);
             } else {
                 keys.push(cur);
             }
-            if !iter.next().is_some_and(|x| x == "") {
-                break;
-            }
         }
 
         Ok(Chal { locks, keys })
     }
 }
 
-pub fn part1(lines: Vec<String>) -> Result<String> {
-    let chal = Chal::parse(lines)?;
+pub fn part1(input: &Input) -> Result<String> {
+    let chal = parsing(|| Chal::parse(input))?;
     let mut total: usize = 0;
     for lock in chal.locks {
         for key in chal.keys.iter() {
@@ -75,8 +84,23 @@ pub fn part1(lines: Vec<String>) -> Result<String> {
     Ok(total.to_string())
 }
 
-pub fn part2(_lines: Vec<String>) -> Result<String> {
-    bail!("part 2 is the friends we made along the way")
+pub fn part2(_input: &Input) -> Result<String> {
+    bail!(Unsolved("part 2 is the friends we made along the way"))
+}
+
+pub struct Day25;
+impl Solver for Day25 {
+    fn day(&self) -> u8 {
+        25
+    }
+
+    fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+        part1(input)
+    }
+
+    fn part2(&self, input: &Input, _params: &Params) -> Result<String> {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -85,8 +109,8 @@ mod test {
     use anyhow::Result;
     use indoc::indoc;
 
-    fn part1_lines() -> Vec<String> {
-        let text = indoc! {"
+    fn part1_input() -> Input {
+        Input::new(indoc! {"
             #####
             .####
             .####
@@ -126,13 +150,12 @@ mod test {
             #.#..
             #.#.#
             #####
-        "};
-        text.lines().map(|x| x.to_string()).collect()
+        "})
     }
 
     #[test]
     fn test_part1_parse() -> Result<()> {
-        let chal = Chal::parse(part1_lines())?;
+        let chal = Chal::parse(&part1_input())?;
         assert_eq!(chal.keys.len(), 3);
         assert_eq!(chal.keys[0].pins, vec![5, 0, 2, 1, 3]);
         assert_eq!(chal.keys[1].pins, vec![4, 3, 4, 0, 2]);
@@ -152,7 +175,7 @@ mod test {
 
     #[test]
     fn test_part1() -> Result<()> {
-        assert_eq!(part1(part1_lines())?, "3");
+        assert_eq!(part1(&part1_input())?, "3");
         Ok(())
     }
 }
@@ -0,0 +1,282 @@
+//! Puzzle examples as test fixtures. `aoc examples` pulls them out of a
+//! saved puzzle page into `examples/NN/`, one `a.txt` per example input
+//! next to an `a.toml` holding the expected answers:
+//!
+//! ```toml
+//! part1 = "12"
+//!
+//! # Examples often need smaller parameters than the real input
+//! [params]
+//! size = "11x7"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use regex::Regex;
+use toml::{Table, Value};
+
+use crate::common::Input;
+use crate::registry;
+use crate::solver::{Params, Part, Unsolved};
+
+/// One example from a puzzle page and the answers given for it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Example {
+    pub input: String,
+    pub answers: BTreeMap<Part, String>,
+}
+
+fn decode_entities(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Finds the examples on a puzzle page. Each `<article>` is a part, and
+/// each `<pre><code>` block an example. An answer is the last emphasised
+/// `<code><em>` after an example, up to the next example or the end of the
+/// part; part 2 usually has no examples of its own, so its answer goes to
+/// the last example of part 1.
+pub fn extract(html: &str) -> Vec<Example> {
+    let article = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+    let token = Regex::new(
+        r"(?s)<pre><code>(?<block>.*?)</code></pre>|<code><em>(?<a>[^<]*)</em></code>|<em><code>(?<b>[^<]*)</code></em>",
+    )
+    .unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+
+    let mut examples: Vec<Example> = Vec::new();
+    // Gives the latest answer seen to the latest example
+    let settle = |examples: &mut Vec<Example>, part, answer: &mut Option<String>| {
+        if let (Some(example), Some(a)) = (examples.last_mut(), answer.take()) {
+            example.answers.insert(part, a);
+        }
+    };
+    for (cap, part) in article.captures_iter(html).zip(Part::both()) {
+        let mut answer: Option<String> = None;
+        for t in token.captures_iter(&cap[1]) {
+            if let Some(block) = t.name("block") {
+                settle(&mut examples, part, &mut answer);
+                examples.push(Example {
+                    input: decode_entities(&tag.replace_all(block.as_str(), "")),
+                    answers: BTreeMap::new(),
+                });
+            } else if let Some(em) = t.name("a").or(t.name("b")) {
+                answer = Some(decode_entities(em.as_str()).trim().to_owned());
+            }
+        }
+        settle(&mut examples, part, &mut answer);
+    }
+    examples
+}
+
+/// `a`, `b`, ... `z`, `aa`, `ab`...
+fn fixture_name(mut i: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'a' + (i % 26) as u8);
+        if i < 26 {
+            break;
+        }
+        i = i / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// Where a day's fixtures live, e.g. `examples/05`
+pub fn day_dir(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("{:02}", day))
+}
+
+/// Writes the examples that have answers as fixtures for `day`, returning
+/// the input files written. Existing fixtures may have hand-edited
+/// parameters, so they're only replaced with `force`.
+pub fn write(dir: &Path, day: u8, examples: &[Example], force: bool) -> Result<Vec<PathBuf>> {
+    let day_dir = day_dir(dir, day);
+    if day_dir.exists() {
+        if !force {
+            bail!(
+                "{} already exists, use --force to replace it",
+                day_dir.display()
+            );
+        }
+        fs::remove_dir_all(&day_dir).with_context(|| format!("removing {}", day_dir.display()))?;
+    }
+    fs::create_dir_all(&day_dir).with_context(|| format!("creating {}", day_dir.display()))?;
+
+    let mut written = Vec::new();
+    for (i, example) in examples
+        .iter()
+        .filter(|e| !e.answers.is_empty())
+        .enumerate()
+    {
+        let path = day_dir.join(format!("{}.txt", fixture_name(i)));
+        fs::write(&path, &example.input).with_context(|| format!("writing {}", path.display()))?;
+        let mut table = Table::new();
+        for (part, answer) in &example.answers {
+            table.insert(format!("part{}", part), Value::String(answer.clone()));
+        }
+        fs::write(path.with_extension("toml"), table.to_string())
+            .with_context(|| format!("writing {}", path.with_extension("toml").display()))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// An example input on disk with its expected answers
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub day: u8,
+    pub path: PathBuf,
+    pub input: Input,
+    pub answers: BTreeMap<Part, String>,
+    /// `key=value` overrides for the day's parameters
+    pub params: Vec<String>,
+}
+
+impl Fixture {
+    /// Reads `path`, an example input, and the `.toml` beside it
+    pub fn load(day: u8, path: &Path) -> Result<Self> {
+        let input = Input::read(path)?;
+        let toml_path = path.with_extension("toml");
+        let text = fs::read_to_string(&toml_path)
+            .with_context(|| format!("reading {}", toml_path.display()))?;
+        let table: Table = text
+            .parse()
+            .with_context(|| format!("in {}", toml_path.display()))?;
+
+        let mut answers = BTreeMap::new();
+        let mut params = Vec::new();
+        for (key, value) in table {
+            match (key.as_str(), value) {
+                ("part1", Value::String(a)) => _ = answers.insert(Part::One, a),
+                ("part2", Value::String(a)) => _ = answers.insert(Part::Two, a),
+                ("params", Value::Table(t)) => {
+                    for (k, v) in t {
+                        let v = match v {
+                            Value::String(s) => s,
+                            v => v.to_string(),
+                        };
+                        params.push(format!("{}={}", k, v));
+                    }
+                }
+                (key, v) => bail!("invalid {} = {} in {}", key, v, toml_path.display()),
+            }
+        }
+        Ok(Self {
+            day,
+            path: path.to_owned(),
+            input,
+            answers,
+            params,
+        })
+    }
+
+    /// Runs each part with an expected answer, describing any that fail.
+    /// Parts the day deliberately leaves unsolved are skipped.
+    pub fn check(&self) -> Result<Vec<String>> {
+        let solver = registry()
+            .iter()
+            .find(|s| s.day() == self.day)
+            .ok_or_else(|| anyhow!("day {} is not registered", self.day))?;
+        let params = Params::with_overrides(solver.params(), &self.params)
+            .with_context(|| format!("params in {}", self.path.display()))?;
+        let mut failures = Vec::new();
+        for (&part, expected) in &self.answers {
+            let failure = match solver.solve(part, &self.input, &params) {
+                Ok(got) if got == *expected => continue,
+                Err(e) if e.is::<Unsolved>() => continue,
+                Ok(got) => format!("expected {}, got {}", expected, got),
+                Err(e) => format!("error: {:#}", e),
+            };
+            failures.push(format!(
+                "{} part {}: {}",
+                self.path.display(),
+                part,
+                failure
+            ));
+        }
+        Ok(failures)
+    }
+}
+
+/// Every fixture under `dir`, from day directories named `NN`
+pub fn load_all(dir: &Path) -> Result<Vec<Fixture>> {
+    let mut fixtures = Vec::new();
+    if !dir.exists() {
+        return Ok(fixtures);
+    }
+    let mut day_dirs: Vec<_> = fs::read_dir(dir)?.collect::<Result<_, _>>()?;
+    day_dirs.sort_by_key(|e| e.file_name());
+    for entry in day_dirs {
+        let Some(day) = entry.file_name().to_str().and_then(|n| n.parse().ok()) else {
+            continue;
+        };
+        let mut paths: Vec<_> = fs::read_dir(entry.path())?
+            .map(|e| e.map(|e| e.path()))
+            .collect::<Result<_, _>>()?;
+        paths.retain(|p| p.extension().is_some_and(|ext| ext == "txt"));
+        paths.sort();
+        for path in paths {
+            fixtures.push(Fixture::load(day, &path)?);
+        }
+    }
+    Ok(fixtures)
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+
+    const PAGE: &str = indoc! {r#"
+        <main>
+        <article class="day-desc"><h2>--- Day 1: Example ---</h2>
+        <p>Some lines, like <code><em>this</em></code>:</p>
+        <pre><code>1 &lt; 2
+        <em>3</em> &amp; 4
+        </code></pre>
+        <p>Part of a bigger one:</p>
+        <pre><code>...
+        </code></pre>
+        <p>The whole thing:</p>
+        <pre><code>5 6
+        </code></pre>
+        <p>Here, the total is <code>1 + 2 = <em>3</em></code>, no, <code><em>11</em></code>.</p>
+        </article>
+        <p>Your puzzle answer was <code>1234</code>.</p>
+        <article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+        <p>Now it's <em><code>31</code></em>.</p>
+        </article>
+        </main>
+    "#};
+
+    #[test]
+    fn test_extract() {
+        let examples = extract(PAGE);
+        assert_eq!(examples.len(), 3);
+        assert_eq!(examples[0].input, "1 < 2\n3 & 4\n");
+        assert!(examples[0].answers.is_empty());
+        assert!(examples[1].answers.is_empty());
+        assert_eq!(examples[2].input, "5 6\n");
+        assert_eq!(
+            examples[2].answers,
+            BTreeMap::from([(Part::One, "11".to_owned()), (Part::Two, "31".to_owned())])
+        );
+        assert!(extract("<html>not a puzzle</html>").is_empty());
+    }
+
+    #[test]
+    fn test_fixture_name() {
+        let names: Vec<_> = [0, 1, 25, 26, 27].into_iter().map(fixture_name).collect();
+        assert_eq!(names, vec!["a", "b", "z", "aa", "ab"]);
+    }
+}
@@ -0,0 +1,59 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::info;
+
+use crate::client::Client;
+use crate::common::Input;
+use crate::runner;
+
+/// Puzzle inputs kept on disk as `<dir>/<year>/NN.txt`. A missing one is
+/// downloaded once and saved, so the site is never asked for it again.
+pub struct InputCache<'a> {
+    dir: PathBuf,
+    year: u16,
+    client: &'a Client,
+}
+
+impl<'a> InputCache<'a> {
+    pub fn new(dir: &Path, year: u16, client: &'a Client) -> Self {
+        Self {
+            dir: dir.to_owned(),
+            year,
+            client,
+        }
+    }
+
+    pub fn path(&self, day: u8) -> PathBuf {
+        runner::input_path(&year_dir(&self.dir, self.year), day)
+    }
+
+    /// The day's input, downloading it first if it isn't cached
+    pub fn load(&self, day: u8) -> Result<Input> {
+        let path = self.path(day);
+        if !path.exists() {
+            let text = self.client.input(self.year, day)?;
+            save(&path, &text)?;
+            info!("saved {}", path.display());
+        }
+        Input::read(&path)
+    }
+}
+
+/// Where a year's inputs live under `dir`, e.g. `inputs/2024`
+pub fn year_dir(dir: &Path, year: u16) -> PathBuf {
+    dir.join(year.to_string())
+}
+
+/// Writes via a temporary file, so an interrupted download never leaves a
+/// truncated input that would be trusted next time
+fn save(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("creating {}", parent.display()))?;
+    }
+    let tmp = path.with_extension("txt.part");
+    fs::write(&tmp, text).with_context(|| format!("writing {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("writing {}", path.display()))?;
+    Ok(())
+}
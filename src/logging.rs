@@ -0,0 +1,166 @@
+//! Diagnostics go through the `log` macros and end up on stderr, leaving
+//! stdout for answers. Each day logs under its module name, so `day20=trace`
+//! turns on everything day 20 has to say without drowning in the others.
+
+use anyhow::{anyhow, bail, Result};
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// `aoc::day22` is shown and matched as just `day22`
+fn short_target(target: &str) -> &str {
+    target.strip_prefix("aoc::").unwrap_or(target)
+}
+
+/// Which messages get through: a default level, overridden per target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    pub fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Default level for `-v` repeated `verbose` times: warnings and errors
+    /// only, then info, debug and trace
+    pub fn from_verbosity(verbose: u8) -> Self {
+        Self::new(match verbose {
+            0 => LevelFilter::Warn,
+            1 => LevelFilter::Info,
+            2 => LevelFilter::Debug,
+            _ => LevelFilter::Trace,
+        })
+    }
+
+    /// Adds comma-separated directives, each `target=level` or a bare level
+    /// that replaces the default, e.g. `day20=trace,common=debug`
+    pub fn parse(&mut self, directives: &str) -> Result<()> {
+        for d in directives
+            .split(',')
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+        {
+            let parse_level = |s: &str| {
+                s.parse::<LevelFilter>().map_err(|_| {
+                    anyhow!(
+                        "invalid log level {:?}, expected off, error, warn, info, debug or trace",
+                        s
+                    )
+                })
+            };
+            match d.split_once('=') {
+                Some((target, level)) => {
+                    let target = short_target(target.trim());
+                    if target.is_empty() {
+                        bail!("missing target in {:?}", d);
+                    }
+                    self.targets
+                        .push((target.to_owned(), parse_level(level.trim())?));
+                }
+                None => self.default = parse_level(d)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// The level for `target`. The longest matching target wins, where
+    /// `day20` also covers `day20::anything`.
+    pub fn level(&self, target: &str) -> LevelFilter {
+        let target = short_target(target);
+        self.targets
+            .iter()
+            .filter(|(t, _)| {
+                target == t
+                    || (target.starts_with(t.as_str()) && target[t.len()..].starts_with("::"))
+            })
+            .max_by_key(|(t, _)| t.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// The most verbose level anything is allowed
+    fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+struct StderrLogger {
+    filter: Filter,
+}
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!(
+                "[{} {}] {}",
+                record.level(),
+                short_target(record.target()),
+                record.args()
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the stderr logger. Can only be done once per process.
+pub fn init(filter: Filter) -> Result<()> {
+    let max_level = filter.max_level();
+    let logger = Box::leak(Box::new(StderrLogger { filter }));
+    log::set_logger(logger).map_err(|e| anyhow!("{}", e))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    #[test]
+    fn test_short_target() {
+        assert_eq!(short_target("aoc::day22"), "day22");
+        assert_eq!(short_target("aoc::common::search"), "common::search");
+        assert_eq!(short_target("other"), "other");
+    }
+
+    #[test]
+    fn test_filter() -> Result<()> {
+        let mut f = Filter::from_verbosity(0);
+        assert_eq!(f.level("aoc::day20"), LevelFilter::Warn);
+        f.parse("day20=trace, common=debug")?;
+        f.parse("common::search=off")?;
+        assert_eq!(f.level("aoc::day20"), LevelFilter::Trace);
+        assert_eq!(f.level("aoc::day2"), LevelFilter::Warn);
+        assert_eq!(f.level("aoc::day200"), LevelFilter::Warn);
+        assert_eq!(f.level("aoc::common"), LevelFilter::Debug);
+        assert_eq!(f.level("aoc::common::grid"), LevelFilter::Debug);
+        assert_eq!(f.level("aoc::common::search"), LevelFilter::Off);
+        assert_eq!(f.max_level(), LevelFilter::Trace);
+
+        f.parse("info")?;
+        assert_eq!(f.level("aoc::day05"), LevelFilter::Info);
+        assert_eq!(Filter::from_verbosity(2).level("x"), LevelFilter::Debug);
+        assert_eq!(Filter::from_verbosity(5).level("x"), LevelFilter::Trace);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_invalid() {
+        let mut f = Filter::new(LevelFilter::Warn);
+        assert!(f.parse("day20=loud").is_err());
+        assert!(f.parse("=trace").is_err());
+        assert!(f.parse("chatty").is_err());
+    }
+}
@@ -1,263 +1,513 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use patharg::InputArg;
 
-mod common;
-mod day01;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-// mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-
-// use crate::common::Pos;
+use aoc::answers::{self, Answers, Verdict};
+use aoc::bench::{self, History};
+use aoc::client::Client;
+use aoc::common::Input;
+use aoc::config::Config;
+use aoc::examples;
+use aoc::inputs::{self, InputCache};
+use aoc::logging::{self, Filter};
+use aoc::runner::{Outcome, Status};
+use aoc::solver::{Params, Part, Solver};
+use aoc::submit::{self, Feedback};
+use aoc::{registry, runner};
 
 #[derive(Debug, Parser)] // requires `derive` feature
 #[command(name = "aoc")]
 #[command(about = "Rusty Advent of Code", long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Cli {
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
+
+    /// Log more to stderr: -v for info, -vv for debug, -vvv for trace
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Log level per target, e.g. `day20=trace` or `day22=debug,day24=info`
+    #[arg(long, value_name = "TARGET=LEVEL", global = true)]
+    log: Vec<String>,
+
+    /// Puzzle year, overriding the config file
+    #[arg(long, global = true)]
+    year: Option<u16>,
+
+    /// Config file with the session token, year and site URL
+    #[arg(long, default_value = "aoc.toml", global = true)]
+    config: PathBuf,
 
-    #[arg(short, long, default_value_t)]
-    input: InputArg,
+    /// Day to run, e.g. `16` or `day16`
+    #[arg(value_parser = parse_day)]
+    day: Option<u8>,
+
+    /// Read the input from this file, or `-` for stdin, instead of
+    /// `inputs/<year>/NN.txt`, which is downloaded if missing
+    #[arg(short, long)]
+    input: Option<InputArg>,
+
+    /// Directory holding the puzzle inputs as `<year>/NN.txt`
+    #[arg(long, default_value = "inputs")]
+    inputs: PathBuf,
 
     /// Run the second part of the daily challenge
     #[arg(short, long)]
     two: bool,
+
+    /// Override one of the day's parameters, see `aoc list`
+    #[arg(short, long = "param", value_name = "KEY=VALUE")]
+    param: Vec<String>,
+
+    /// Log each step to stderr, for days with a `trace` parameter. Shorthand
+    /// for `-p trace=FORMAT`, where FORMAT is plain or json (one object per line)
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "plain"
+    )]
+    trace: Option<String>,
+
+    /// Also write the day's data as FORMAT, for days with an `export`
+    /// parameter. Shorthand for `-p export=FORMAT`
+    #[arg(long, value_name = "FORMAT")]
+    export: Option<String>,
+
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+/// How answers are printed on stdout
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+enum Format {
+    /// For people
+    #[default]
+    Plain,
+    /// One JSON object per answer, for scripts
+    Json,
 }
 
 #[derive(Debug, Subcommand)]
 enum Commands {
-    Day01,
-    Day05,
-    Day06,
-    Day07,
-    Day08,
-    Day09,
-    Day10,
-    Day11,
-    Day12,
-    Day13,
-    // Day14,
-    Day15,
-    Day16,
-    Day17,
-    Day18,
-    Day19,
-    Day20,
-    Day21,
-    Day22,
-    Day23,
-    Day24,
-    Day25,
+    /// List the registered days
+    List,
+    /// Run both parts of every registered day and summarise the results
+    All {
+        /// Directory holding the puzzle inputs as `<year>/NN.txt`. Missing
+        /// ones aren't downloaded, run the day on its own to fetch it
+        #[arg(long, default_value = "inputs")]
+        inputs: PathBuf,
+
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+    /// Check answers against those locked in the answers file
+    Verify {
+        /// Only verify this day
+        #[arg(value_parser = parse_day)]
+        day: Option<u8>,
+
+        /// Directory holding the puzzle inputs as `<year>/NN.txt`. Missing
+        /// ones aren't downloaded, run the day on its own to fetch it
+        #[arg(long, default_value = "inputs")]
+        inputs: PathBuf,
+
+        #[arg(long, default_value = "answers.toml")]
+        answers: PathBuf,
+
+        /// Lock in answers for parts that don't have one yet
+        #[arg(long)]
+        record: bool,
+    },
+    /// Time a day's solver over several iterations
+    Bench {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+
+        /// Benchmark the second part
+        #[arg(short, long)]
+        two: bool,
+
+        /// Timed runs, at least one
+        #[arg(long, default_value_t = 10, value_parser = parse_iterations)]
+        iterations: usize,
+
+        /// Untimed runs before measuring
+        #[arg(long, default_value_t = 1)]
+        warmup: usize,
+
+        /// Directory holding the puzzle inputs as `<year>/NN.txt`. Missing
+        /// ones aren't downloaded, run the day on its own to fetch it
+        #[arg(long, default_value = "inputs")]
+        inputs: PathBuf,
+
+        /// Previous results to compare against
+        #[arg(long, default_value = "bench.toml")]
+        results: PathBuf,
+
+        /// Store this run in the results file
+        #[arg(long)]
+        save: bool,
+
+        /// Override one of the day's parameters, see `aoc list`
+        #[arg(short, long = "param", value_name = "KEY=VALUE")]
+        param: Vec<String>,
+    },
+    /// Save the examples from a puzzle page as test fixtures
+    Examples {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+
+        /// The puzzle page saved from a browser [default: puzzles/<year>/NN.html]
+        #[arg(long)]
+        page: Option<PathBuf>,
+
+        /// Directory holding the fixtures as `NN/a.txt` and `NN/a.toml`
+        #[arg(long, default_value = "examples")]
+        out: PathBuf,
+
+        /// Replace the day's existing fixtures
+        #[arg(long)]
+        force: bool,
+    },
+    /// Solve a day and submit the answer to the site
+    Submit {
+        #[arg(value_parser = parse_day)]
+        day: u8,
+
+        /// Submit the second part
+        #[arg(short, long)]
+        two: bool,
+
+        /// Directory holding the puzzle inputs as `<year>/NN.txt`
+        #[arg(long, default_value = "inputs")]
+        inputs: PathBuf,
+
+        /// Every answer submitted so far, to avoid repeating wrong ones
+        #[arg(long, default_value = "submissions.toml")]
+        history: PathBuf,
+
+        /// Override one of the day's parameters, see `aoc list`
+        #[arg(short, long = "param", value_name = "KEY=VALUE")]
+        param: Vec<String>,
+    },
 }
 
-fn line_vec(input: InputArg) -> Result<Vec<String>> {
-    let mut the_vec: Vec<String> = Vec::new();
-    for r in input.lines()? {
-        the_vec.push(r?);
-    }
-    Ok(the_vec)
+fn parse_day(s: &str) -> Result<u8> {
+    let n = s.strip_prefix("day").unwrap_or(s);
+    Ok(n.parse()?)
 }
 
-fn main() -> Result<()> {
-    let args = Cli::parse();
+fn parse_iterations(s: &str) -> Result<usize> {
+    match s.parse()? {
+        0 => bail!("need at least one iteration to time"),
+        n => Ok(n),
+    }
+}
 
-    let r: Result<String> = match args.command {
-        Commands::Day01 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day01::part1(lines)
-            } else {
-                day01::part2(lines)
-            }
-        }
-        Commands::Day05 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day05::part1(lines)
-            } else {
-                day05::part2(lines)
-            }
-        }
-        Commands::Day06 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day06::part1(lines)
-            } else {
-                day06::part2(lines)
-            }
-        }
-        Commands::Day07 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day07::part1(lines)
-            } else {
-                day07::part2(lines)
-            }
-        }
-        Commands::Day08 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day08::part1(lines)
-            } else {
-                day08::part2(lines)
-            }
+fn list() {
+    for s in registry() {
+        println!("{:>2} {}", s.day(), s.name());
+        for p in s.params() {
+            println!(
+                "{:>5}{:<24} {}",
+                "",
+                format!("{}={}", p.key, p.default),
+                p.help
+            );
         }
-        Commands::Day09 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day09::part1(lines)
-            } else {
-                day09::part2(lines)
-            }
-        }
-        Commands::Day10 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day10::part1(lines)
-            } else {
-                day10::part2(lines)
-            }
-        }
-        Commands::Day11 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day11::part1(lines, 25)
-            } else {
-                day11::part2(lines, 75)
-            }
-        }
-        Commands::Day12 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day12::part1(lines)
-            } else {
-                day12::part2(lines)
-            }
-        }
-        Commands::Day13 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day13::part1(lines)
-            } else {
-                day13::part2(lines)
-            }
-        }
-        // Commands::Day14 => {
-        //     let lines = line_vec(args.input)?;
-        //     if !args.two {
-        //         day14::part1(lines, Pos::new(101, 103)?)
-        //     } else {
-        //         day14::part2(lines, Pos::new(101, 103)?)
-        //     }
-        // }
-        Commands::Day15 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day15::part1(lines)
-            } else {
-                day15::part2(lines)
-            }
-        }
-        Commands::Day16 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day16::part1(lines)
-            } else {
-                day16::part2(lines)
-            }
-        }
-        Commands::Day17 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day17::part1(lines)
-            } else {
-                day17::part2(lines)
-            }
-        }
-        Commands::Day18 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day18::part1(lines)
-            } else {
-                day18::part2(lines)
-            }
-        }
-        Commands::Day19 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day19::part1(lines)
-            } else {
-                day19::part2(lines)
-            }
-        }
-        Commands::Day20 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day20::part1(lines)
+    }
+}
+
+fn find(day: u8) -> Result<&'static dyn Solver> {
+    registry()
+        .iter()
+        .copied()
+        .find(|s| s.day() == day)
+        .ok_or_else(|| anyhow!("day {} is not registered, see `aoc list`", day))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bench(
+    day: u8,
+    two: bool,
+    iterations: usize,
+    warmup: usize,
+    inputs: PathBuf,
+    results: PathBuf,
+    save: bool,
+    param: Vec<String>,
+) -> Result<()> {
+    let solver = find(day)?;
+    let part = if two { Part::Two } else { Part::One };
+    let input = runner::input_path(&inputs, day);
+    let params = Params::with_overrides(solver.params(), &param)?;
+    let r = bench::bench(solver, part, &input, &params, warmup, iterations)?;
+
+    println!(
+        "{} part {}: {} iterations ({} warm-up)",
+        solver.name(),
+        part,
+        iterations,
+        warmup
+    );
+    println!("{:>6} {:>10} {:>10} {:>10}", "", "min", "median", "max");
+    for (label, stats) in [("load", r.load), ("parse", r.parse), ("solve", r.solve)] {
+        println!(
+            "{:>6} {:>10} {:>10} {:>10}",
+            label,
+            format!("{:.1?}", stats.min),
+            format!("{:.1?}", stats.median),
+            format!("{:.1?}", stats.max)
+        );
+    }
+
+    let mut history = History::load(&results)?;
+    if let Some(prev) = history.get(day, part)? {
+        println!(
+            "median solve {:.1?} vs {:.1?} previously ({:+.1}%){}",
+            r.solve.median,
+            prev.solve.median,
+            r.change(&prev) * 100.,
+            if r.is_regression(&prev) {
+                " REGRESSION"
             } else {
-                day20::part2(lines)
+                ""
             }
+        );
+    }
+    if save {
+        history.insert(day, part, &r);
+        history.save(&results)?;
+    }
+    Ok(())
+}
+
+/// Why a part has no answer
+fn status_message(status: &Status) -> String {
+    match status {
+        Status::Failed(e) => format!("error: {}", e),
+        Status::NoInput => "no input".to_owned(),
+        Status::NotSolved | Status::Ok => "not solved".to_owned(),
+    }
+}
+
+fn verify(day: Option<u8>, inputs: PathBuf, answers_path: PathBuf, record: bool) -> Result<()> {
+    let mut answers = Answers::load(&answers_path)?;
+    let solvers: Vec<_> = registry()
+        .iter()
+        .copied()
+        .filter(|s| day.is_none_or(|d| s.day() == d))
+        .collect();
+    let outcomes = runner::run_all(&solvers, &inputs);
+
+    let mut failures = 0;
+    let mut recorded = 0;
+    for o in outcomes.iter() {
+        let verdict = answers::verify(o, &answers);
+        if verdict.is_failure() {
+            failures += 1;
         }
-        Commands::Day21 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day21::part1(lines)
-            } else {
-                day21::part2(lines)
+        let msg = match verdict {
+            Verdict::Match => "ok".to_owned(),
+            Verdict::Mismatch { expected, got } => {
+                format!("MISMATCH: expected {}, got {}", expected, got)
             }
+            Verdict::Unknown => match &o.answer {
+                Some(answer) if record && answers.record(o.day, o.part, answer) => {
+                    recorded += 1;
+                    format!("recorded {}", answer)
+                }
+                Some(answer) => format!("unverified {}", answer),
+                None => status_message(&o.status),
+            },
+            Verdict::NoAnswer(status) => status_message(&status),
+        };
+        println!("{:>3} {:>4} {}", o.day, o.part, msg);
+    }
+
+    if recorded > 0 {
+        answers.save(&answers_path)?;
+    }
+    if failures > 0 {
+        bail!("{} part(s) failed verification", failures);
+    }
+    Ok(())
+}
+
+fn save_examples(
+    config: &Config,
+    day: u8,
+    page: Option<PathBuf>,
+    out: PathBuf,
+    force: bool,
+) -> Result<()> {
+    find(day)?;
+    let page =
+        page.unwrap_or_else(|| PathBuf::from(format!("puzzles/{}/{:02}.html", config.year, day)));
+    let html = fs::read_to_string(&page).with_context(|| format!("reading {}", page.display()))?;
+    let found = examples::extract(&html);
+    let written = examples::write(&out, day, &found, force)?;
+    for path in &written {
+        println!("{}", path.display());
+    }
+    println!(
+        "{} of {} examples had answers; add any [params] they need to the .toml files",
+        written.len(),
+        found.len()
+    );
+    Ok(())
+}
+
+fn submit(
+    config: &Config,
+    day: u8,
+    two: bool,
+    inputs: PathBuf,
+    history_path: PathBuf,
+    param: Vec<String>,
+) -> Result<()> {
+    let solver = find(day)?;
+    let part = if two { Part::Two } else { Part::One };
+    let client = Client::from_config(config);
+    let input = InputCache::new(&inputs, config.year, &client).load(day)?;
+    let params = Params::with_overrides(solver.params(), &param)?;
+    let answer = solver.solve(part, &input, &params)?;
+    let answer = answer.trim();
+
+    let mut history = submit::History::load(&history_path)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    history
+        .check(config.year, day, part, answer, now)
+        .with_context(|| format!("not submitting {}", answer))?;
+    let reply = client.submit(config.year, day, part, answer)?;
+    history.record(config.year, day, part, answer, &reply, now);
+    history.save(&history_path)?;
+
+    println!("{}", reply.message);
+    match reply.feedback {
+        Feedback::Right => Ok(()),
+        feedback => bail!("day {} part {}: {} was {}", day, part, answer, feedback),
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Cli::parse();
+    let mut filter = Filter::from_verbosity(args.verbose);
+    for directives in &args.log {
+        filter.parse(directives)?;
+    }
+    logging::init(filter)?;
+    let mut config = Config::load(&args.config)?;
+    if let Some(year) = args.year {
+        config.year = year;
+    }
+
+    let day = match (args.command, args.day) {
+        (Some(Commands::List), _) => {
+            list();
+            return Ok(());
         }
-        Commands::Day22 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day22::part1(lines)
-            } else {
-                day22::part2(lines)
+        (Some(Commands::All { inputs, format }), _) => {
+            let outcomes = runner::run_all(registry(), &inputs::year_dir(&inputs, config.year));
+            match format {
+                Format::Plain => print!("{}", runner::summary_table(&outcomes)),
+                Format::Json => {
+                    for o in &outcomes {
+                        println!("{}", o.to_json());
+                    }
+                }
             }
+            return Ok(());
         }
-        Commands::Day23 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day23::part1(lines)
-            } else {
-                day23::part2(lines)
-            }
+        (
+            Some(Commands::Verify {
+                day,
+                inputs,
+                answers,
+                record,
+            }),
+            _,
+        ) => return verify(day, inputs::year_dir(&inputs, config.year), answers, record),
+        (
+            Some(Commands::Bench {
+                day,
+                two,
+                iterations,
+                warmup,
+                inputs,
+                results,
+                save,
+                param,
+            }),
+            _,
+        ) => {
+            let inputs = inputs::year_dir(&inputs, config.year);
+            return bench(day, two, iterations, warmup, inputs, results, save, param);
         }
-        Commands::Day24 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day24::part1(lines)
-            } else {
-                day24::part2(lines)
+        (
+            Some(Commands::Submit {
+                day,
+                two,
+                inputs,
+                history,
+                param,
+            }),
+            _,
+        ) => return submit(&config, day, two, inputs, history, param),
+        (
+            Some(Commands::Examples {
+                day,
+                page,
+                out,
+                force,
+            }),
+            _,
+        ) => return save_examples(&config, day, page, out, force),
+        (None, Some(day)) => day,
+        (None, None) => return Err(anyhow!("specify a day to run, or `list`")),
+    };
+    let solver = find(day)?;
+
+    let mut param = args.param;
+    for (key, value) in [("trace", args.trace), ("export", args.export)] {
+        if let Some(value) = value {
+            if !solver.params().iter().any(|p| p.key == key) {
+                bail!("day {} has no {} mode", day, key);
             }
+            param.push(format!("{}={}", key, value));
         }
-        Commands::Day25 => {
-            let lines = line_vec(args.input)?;
-            if !args.two {
-                day25::part1(lines)
-            } else {
-                day25::part2(lines)
-            }
+    }
+    let params = Params::with_overrides(solver.params(), &param)?;
+    let input = match args.input {
+        Some(path) => Input::new(&path.read_to_string()?),
+        None => {
+            let client = Client::from_config(&config);
+            InputCache::new(&args.inputs, config.year, &client).load(day)?
         }
     };
-    println!("Result: {}", r?);
+    let part = if args.two { Part::Two } else { Part::One };
+    let start = Instant::now();
+    let answer = solver.solve(part, &input, &params)?;
+    let elapsed = start.elapsed();
+    match args.format {
+        Format::Plain => println!("Result: {}", answer),
+        Format::Json => {
+            let o = Outcome {
+                day,
+                part,
+                answer: Some(answer),
+                elapsed,
+                status: Status::Ok,
+            };
+            println!("{}", o.to_json());
+        }
+    }
     Ok(())
 }
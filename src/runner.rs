@@ -0,0 +1,292 @@
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::common::Input;
+use crate::solver::{Params, Part, Solver, Unsolved};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    NotSolved,
+    NoInput,
+    Failed(String),
+}
+
+/// Result of running one part of one day.
+#[derive(Debug, Clone)]
+pub struct Outcome {
+    pub day: u8,
+    pub part: Part,
+    pub answer: Option<String>,
+    pub elapsed: Duration,
+    pub status: Status,
+}
+
+/// `s` as a quoted JSON string
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => _ = write!(out, "\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Outcome {
+    /// One line of JSON for scripts, e.g.
+    /// `{"day":5,"part":1,"answer":"143","elapsed_ms":2.1,"status":"ok"}`.
+    /// A missing answer is `null`, and failures also carry an `error`.
+    pub fn to_json(&self) -> String {
+        let status = match &self.status {
+            Status::Ok => "ok",
+            Status::NotSolved => "not solved",
+            Status::NoInput => "no input",
+            Status::Failed(_) => "error",
+        };
+        let mut fields = vec![
+            format!(r#""day":{}"#, self.day),
+            format!(r#""part":{}"#, self.part),
+            format!(
+                r#""answer":{}"#,
+                self.answer
+                    .as_deref()
+                    .map_or("null".to_owned(), json_string)
+            ),
+            format!(r#""elapsed_ms":{}"#, self.elapsed.as_secs_f64() * 1000.),
+            format!(r#""status":{}"#, json_string(status)),
+        ];
+        if let Status::Failed(e) = &self.status {
+            fields.push(format!(r#""error":{}"#, json_string(e)));
+        }
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Where `aoc all` expects a day's puzzle input within a year's inputs,
+/// e.g. `inputs/2024/05.txt`
+pub fn input_path(dir: &Path, day: u8) -> PathBuf {
+    dir.join(format!("{:02}.txt", day))
+}
+
+pub fn run(solver: &dyn Solver, part: Part, input: &Input, params: &Params) -> Outcome {
+    let start = Instant::now();
+    let r = solver.solve(part, input, params);
+    let elapsed = start.elapsed();
+    let (answer, status) = match r {
+        Ok(answer) => (Some(answer), Status::Ok),
+        Err(e) if e.is::<Unsolved>() => (None, Status::NotSolved),
+        Err(e) => (None, Status::Failed(format!("{:#}", e))),
+    };
+    Outcome {
+        day: solver.day(),
+        part,
+        answer,
+        elapsed,
+        status,
+    }
+}
+
+/// Runs both parts of every solver against its input in `dir`. Failures are
+/// recorded in the outcome rather than stopping the run.
+pub fn run_all(solvers: &[&dyn Solver], dir: &Path) -> Vec<Outcome> {
+    let mut outcomes = Vec::new();
+    for solver in solvers {
+        let path = input_path(dir, solver.day());
+        // Only a missing file means no input; one that can't be read is a
+        // failure worth seeing
+        let input = path.exists().then(|| Input::read(&path));
+        let params = solver.default_params();
+        for part in Part::both() {
+            let status = match &input {
+                Some(Ok(input)) => {
+                    outcomes.push(run(*solver, part, input, &params));
+                    continue;
+                }
+                Some(Err(e)) => Status::Failed(format!("{:#}", e)),
+                None => Status::NoInput,
+            };
+            outcomes.push(Outcome {
+                day: solver.day(),
+                part,
+                answer: None,
+                elapsed: Duration::ZERO,
+                status,
+            });
+        }
+    }
+    outcomes
+}
+
+pub fn summary_table(outcomes: &[Outcome]) -> String {
+    let answer_w = outcomes
+        .iter()
+        .filter_map(|o| o.answer.as_ref().map(|a| a.len()))
+        .max()
+        .unwrap_or(0)
+        .max("answer".len());
+    let mut out = String::new();
+    _ = writeln!(
+        out,
+        "day part {:<answer_w$} {:>10} status",
+        "answer", "time"
+    );
+    for o in outcomes {
+        let time = match o.status {
+            Status::NoInput => String::new(),
+            _ => format!("{:.1?}", o.elapsed),
+        };
+        let status = match &o.status {
+            Status::Ok => "ok".to_owned(),
+            Status::NotSolved => "not solved".to_owned(),
+            Status::NoInput => "no input".to_owned(),
+            // Parse errors go on to quote the input, which won't fit a row
+            Status::Failed(e) => format!("error: {}", e.lines().next().unwrap_or("")),
+        };
+        _ = writeln!(
+            out,
+            "{:>3} {:>4} {:<answer_w$} {:>10} {}",
+            o.day,
+            o.part,
+            o.answer.as_deref().unwrap_or(""),
+            time,
+            status
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::{bail, Result};
+
+    use super::*;
+
+    struct Fake;
+    impl Solver for Fake {
+        fn day(&self) -> u8 {
+            99
+        }
+
+        fn part1(&self, input: &Input, _params: &Params) -> Result<String> {
+            Ok(input.lines().count().to_string())
+        }
+
+        fn part2(&self, _input: &Input, _params: &Params) -> Result<String> {
+            bail!(Unsolved("someday"))
+        }
+    }
+
+    struct Broken;
+    impl Solver for Broken {
+        fn day(&self) -> u8 {
+            98
+        }
+
+        fn part1(&self, _input: &Input, _params: &Params) -> Result<String> {
+            bail!("oops")
+        }
+
+        fn part2(&self, _input: &Input, _params: &Params) -> Result<String> {
+            bail!("oops")
+        }
+    }
+
+    #[test]
+    fn test_run_status() {
+        let input = Input::new("a\nb\n");
+        let params = Params::default();
+
+        let o = run(&Fake, Part::One, &input, &params);
+        assert_eq!(o.status, Status::Ok);
+        assert_eq!(o.answer.as_deref(), Some("2"));
+
+        let o = run(&Fake, Part::Two, &input, &params);
+        assert_eq!(o.status, Status::NotSolved);
+        assert_eq!(o.answer, None);
+
+        let o = run(&Broken, Part::One, &input, &params);
+        assert_eq!(o.status, Status::Failed("oops".to_owned()));
+    }
+
+    #[test]
+    fn test_run_all_missing_input() {
+        let outcomes = run_all(&[&Fake], Path::new("/nonexistent"));
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|o| o.status == Status::NoInput));
+    }
+
+    #[test]
+    fn test_run_all_unreadable_input() -> Result<()> {
+        // A directory where the input should be can't be read as one
+        let dir = std::env::temp_dir().join(format!("aoc-runner-{}", std::process::id()));
+        let path = input_path(&dir, 99);
+        std::fs::create_dir_all(&path)?;
+        let outcomes = run_all(&[&Fake], &dir);
+        std::fs::remove_dir_all(&dir)?;
+        assert_eq!(outcomes.len(), 2);
+        for o in outcomes {
+            let Status::Failed(e) = o.status else {
+                panic!("expected a failure, got {:?}", o.status);
+            };
+            assert!(
+                e.starts_with(&format!("reading {}", path.display())),
+                "{}",
+                e
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_json() {
+        let mut o = Outcome {
+            day: 17,
+            part: Part::One,
+            answer: Some("4,6,3".to_owned()),
+            elapsed: Duration::from_micros(2500),
+            status: Status::Ok,
+        };
+        assert_eq!(
+            o.to_json(),
+            r#"{"day":17,"part":1,"answer":"4,6,3","elapsed_ms":2.5,"status":"ok"}"#
+        );
+        o.answer = None;
+        o.status = Status::Failed("bad \"input\"\nline 2".to_owned());
+        assert_eq!(
+            o.to_json(),
+            r#"{"day":17,"part":1,"answer":null,"elapsed_ms":2.5,"status":"error","error":"bad \"input\"\nline 2"}"#
+        );
+    }
+
+    #[test]
+    fn test_summary_table() {
+        let outcomes = vec![
+            Outcome {
+                day: 5,
+                part: Part::One,
+                answer: Some("143".to_owned()),
+                elapsed: Duration::from_millis(2),
+                status: Status::Ok,
+            },
+            Outcome {
+                day: 25,
+                part: Part::Two,
+                answer: None,
+                elapsed: Duration::ZERO,
+                status: Status::NotSolved,
+            },
+        ];
+        let table = summary_table(&outcomes);
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows[0], "day part answer       time status");
+        assert_eq!(rows[1], "  5    1 143         2.0ms ok");
+        assert_eq!(rows[2], " 25    2             0.0ns not solved");
+    }
+}
@@ -0,0 +1,282 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+
+use crate::common::Input;
+
+/// A puzzle constant that a day bakes into its solution, e.g. grid size or
+/// number of iterations, along with the value the real puzzle uses.
+#[derive(Debug, Clone, Copy)]
+pub struct Param {
+    pub key: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+    /// Vets an override up front, so a bad value is reported before the
+    /// day runs rather than going wrong part way through it
+    pub check: fn(&str) -> Result<()>,
+}
+
+impl Param {
+    /// Any value that parses as a `T`
+    pub fn parses<T>(value: &str) -> Result<()>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        value.parse::<T>().map_err(|e| anyhow!("{}", e))?;
+        Ok(())
+    }
+
+    /// A whole number no smaller than `MIN`
+    pub fn at_least<const MIN: usize>(value: &str) -> Result<()> {
+        let n: usize = value.parse()?;
+        if n < MIN {
+            bail!("must be at least {}", MIN);
+        }
+        Ok(())
+    }
+}
+
+/// Parameter values handed to a solver.
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    values: HashMap<&'static str, String>,
+}
+
+impl Params {
+    pub fn defaults(declared: &[Param]) -> Self {
+        let values = declared
+            .iter()
+            .map(|p| (p.key, p.default.to_owned()))
+            .collect();
+        Self { values }
+    }
+
+    /// The defaults for `declared`, with some replaced by `key=value`
+    /// overrides. Keys the day doesn't declare and values that fail the
+    /// parameter's check are rejected.
+    pub fn with_overrides<S: AsRef<str>>(declared: &[Param], overrides: &[S]) -> Result<Self> {
+        let mut params = Self::defaults(declared);
+        for o in overrides {
+            let o = o.as_ref();
+            let Some((key, value)) = o.split_once('=') else {
+                bail!("expected key=value, got {}", o);
+            };
+            let Some(param) = declared.iter().find(|p| p.key == key.trim()) else {
+                let known: Vec<_> = declared.iter().map(|p| p.key).collect();
+                if known.is_empty() {
+                    bail!("unknown parameter {}, this day takes none", key);
+                }
+                bail!(
+                    "unknown parameter {}, expected one of {}",
+                    key,
+                    known.join(", ")
+                );
+            };
+            let value = value.trim();
+            (param.check)(value)
+                .map_err(|e| anyhow!("invalid value {:?} for parameter {}: {}", value, key, e))?;
+            params.values.insert(param.key, value.to_owned());
+        }
+        Ok(params)
+    }
+
+    pub fn get<T>(&self, key: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        let value = self
+            .values
+            .get(key)
+            .ok_or_else(|| anyhow!("undeclared parameter {}", key))?;
+        value
+            .parse()
+            .map_err(|e| anyhow!("invalid value {:?} for parameter {}: {}", value, key, e))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+impl Part {
+    pub fn both() -> [Self; 2] {
+        [Self::One, Self::Two]
+    }
+}
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::One => f.pad("1"),
+            Self::Two => f.pad("2"),
+        }
+    }
+}
+
+/// Error for a part that is deliberately left without a solution, as opposed
+/// to one that failed.
+#[derive(Debug)]
+pub struct Unsolved(pub &'static str);
+impl Display for Unsolved {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "not solved: {}", self.0)
+    }
+}
+impl std::error::Error for Unsolved {}
+
+/// One day of the calendar. Each `dayNN` module implements this and is listed
+/// once in the registry via `days!`.
+pub trait Solver: Sync {
+    fn day(&self) -> u8;
+
+    fn name(&self) -> String {
+        format!("day{:02}", self.day())
+    }
+
+    fn part1(&self, input: &Input, params: &Params) -> Result<String>;
+
+    fn part2(&self, input: &Input, params: &Params) -> Result<String>;
+
+    /// Constants used by `part1`/`part2`, with the values for the real puzzle
+    fn params(&self) -> &'static [Param] {
+        &[]
+    }
+
+    fn default_params(&self) -> Params {
+        Params::defaults(self.params())
+    }
+
+    fn solve(&self, part: Part, input: &Input, params: &Params) -> Result<String> {
+        match part {
+            Part::One => self.part1(input, params),
+            Part::Two => self.part2(input, params),
+        }
+    }
+}
+
+thread_local! {
+    static PARSE_TIME: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+}
+
+/// Runs `parse`, a day's parsing of its input, adding the time it takes to
+/// this thread's total so `bench` can time parsing apart from solving. Days
+/// wrap each call that turns input text into their own types.
+pub fn parsing<T>(parse: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let parsed = parse();
+    PARSE_TIME.with(|t| t.set(t.get() + start.elapsed()));
+    parsed
+}
+
+/// Time spent in `parsing` on this thread since the last call
+pub fn take_parse_time() -> Duration {
+    PARSE_TIME.with(|t| t.replace(Duration::ZERO))
+}
+
+/// Builds the registry from the day solvers. A new day needs its `pub mod`
+/// next to the others in lib.rs as well as a line here.
+macro_rules! days {
+    ($($module:ident :: $solver:ident),* $(,)?) => {
+        /// All registered solvers, in day order
+        pub fn registry() -> &'static [&'static dyn crate::solver::Solver] {
+            &[$(&$module::$solver),*]
+        }
+    };
+}
+pub(crate) use days;
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param {
+            key: "size",
+            default: "71",
+            help: "grid size",
+            check: Param::at_least::<1>,
+        },
+        Param {
+            key: "label",
+            default: "abc",
+            help: "a label",
+            check: Param::parses::<String>,
+        },
+    ];
+
+    #[test]
+    fn test_params_defaults() -> Result<()> {
+        let params = Params::defaults(PARAMS);
+        assert_eq!(params.get::<usize>("size")?, 71);
+        assert_eq!(params.get::<String>("label")?, "abc");
+        Ok(())
+    }
+
+    #[test]
+    fn test_params_errors() {
+        let params = Params::defaults(PARAMS);
+        assert!(params.get::<usize>("nope").is_err());
+        assert!(params.get::<usize>("label").is_err());
+    }
+
+    #[test]
+    fn test_params_overrides() -> Result<()> {
+        let params = Params::with_overrides(PARAMS, &["size=7", "label = x=y"])?;
+        assert_eq!(params.get::<usize>("size")?, 7);
+        assert_eq!(params.get::<String>("label")?, "x=y");
+        assert!(Params::with_overrides(PARAMS, &["nope=1"]).is_err());
+        assert!(Params::with_overrides(PARAMS, &["size"]).is_err());
+        assert!(Params::with_overrides(&[], &["size=1"]).is_err());
+        let e = Params::with_overrides(PARAMS, &["size=big"]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "invalid value \"big\" for parameter size: invalid digit found in string"
+        );
+        let e = Params::with_overrides(PARAMS, &["size=0"]).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "invalid value \"0\" for parameter size: must be at least 1"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_registry() {
+        let days: Vec<u8> = crate::registry().iter().map(|s| s.day()).collect();
+        let mut sorted = days.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(days, sorted, "registry should be sorted with no duplicates");
+
+        // A day missing from `days!` would otherwise just never run
+        let src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut modules: Vec<u8> = std::fs::read_dir(src)
+            .unwrap()
+            .filter_map(|e| {
+                let name = e.unwrap().file_name().into_string().ok()?;
+                name.strip_prefix("day")?.strip_suffix(".rs")?.parse().ok()
+            })
+            .collect();
+        modules.sort();
+        assert_eq!(days, modules, "every src/dayNN.rs should be registered");
+    }
+
+    #[test]
+    fn test_default_params_pass_checks() {
+        for solver in crate::registry() {
+            for p in solver.params() {
+                if let Err(e) = (p.check)(p.default) {
+                    panic!("{} default {}={}: {}", solver.name(), p.key, p.default, e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,492 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use toml::{Table, Value};
+
+use crate::solver::Part;
+
+/// What the site made of a submitted answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Feedback {
+    Right,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Answered too soon after the last attempt; the answer wasn't checked
+    RateLimited,
+    /// The part was already solved, or part 1 isn't yet
+    WrongLevel,
+}
+
+impl Feedback {
+    /// Whether the answer was checked and found wrong
+    pub fn is_wrong(self) -> bool {
+        matches!(self, Self::Wrong | Self::TooHigh | Self::TooLow)
+    }
+}
+
+impl Display for Feedback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            Self::Right => "right",
+            Self::Wrong => "wrong",
+            Self::TooHigh => "too high",
+            Self::TooLow => "too low",
+            Self::RateLimited => "rate limited",
+            Self::WrongLevel => "wrong level",
+        })
+    }
+}
+
+impl FromStr for Feedback {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "right" => Self::Right,
+            "wrong" => Self::Wrong,
+            "too high" => Self::TooHigh,
+            "too low" => Self::TooLow,
+            "rate limited" => Self::RateLimited,
+            "wrong level" => Self::WrongLevel,
+            _ => bail!("invalid feedback {:?}", s),
+        })
+    }
+}
+
+/// The site's response to a submission
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub feedback: Feedback,
+    /// How long until the site accepts another answer
+    pub wait: Option<Duration>,
+    /// The message as shown on the page, without markup
+    pub message: String,
+}
+
+impl Reply {
+    /// Reads the verdict out of the page returned after posting an answer
+    pub fn parse(html: &str) -> Result<Self> {
+        let article = Regex::new(r"(?s)<article[^>]*>(.*?)</article>").unwrap();
+        let tag = Regex::new(r"<[^>]*>").unwrap();
+        let body = article
+            .captures(html)
+            .map_or(html, |c| c.get(1).unwrap().as_str());
+        let message = tag.replace_all(body, "");
+        let message = message.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let feedback = if message.contains("That's the right answer") {
+            Feedback::Right
+        } else if message.contains("your answer is too high") {
+            Feedback::TooHigh
+        } else if message.contains("your answer is too low") {
+            Feedback::TooLow
+        } else if message.contains("That's not the right answer") {
+            Feedback::Wrong
+        } else if message.contains("You gave an answer too recently") {
+            Feedback::RateLimited
+        } else if message.contains("solving the right level") {
+            Feedback::WrongLevel
+        } else {
+            bail!("unrecognised response: {}", message);
+        };
+        Ok(Self {
+            feedback,
+            wait: parse_wait(&message),
+            message,
+        })
+    }
+}
+
+/// The delay in messages like "You have 1m 5s left to wait" or "please wait
+/// 5 minutes before trying again"
+fn parse_wait(message: &str) -> Option<Duration> {
+    let left = Regex::new(r"You have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+    if let Some(cap) = left.captures(message) {
+        let minutes: u64 = cap.get(1).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        let seconds: u64 = cap[2].parse().ok()?;
+        return Some(Duration::from_secs(minutes * 60 + seconds));
+    }
+    let minutes = Regex::new(r"wait (one|\d+) minutes? before trying again").unwrap();
+    let cap = minutes.captures(message)?;
+    let n = match &cap[1] {
+        "one" => 1,
+        n => n.parse().ok()?,
+    };
+    Some(Duration::from_secs(n * 60))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attempt {
+    pub answer: String,
+    pub feedback: Feedback,
+    /// Seconds since the Unix epoch
+    pub at: u64,
+}
+
+/// Every answer submitted, so a known-wrong one is never sent twice. Stored
+/// as TOML keyed by year, day and part:
+///
+/// ```toml
+/// not_before = 1733040123
+///
+/// [[2024.day01.part1]]
+/// answer = "1234"
+/// at = 1733040063
+/// feedback = "too low"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct History {
+    attempts: BTreeMap<(u16, u8, Part), Vec<Attempt>>,
+    /// When the site will next accept an answer, in seconds since the epoch
+    not_before: Option<u64>,
+}
+
+fn part_key(part: Part) -> String {
+    format!("part{}", part)
+}
+
+impl History {
+    pub fn parse(text: &str) -> Result<Self> {
+        let table: Table = text.parse()?;
+        let mut history = Self::default();
+        for (key, value) in table {
+            if key == "not_before" {
+                let at = value
+                    .as_integer()
+                    .ok_or_else(|| anyhow!("not_before should be a timestamp"))?;
+                history.not_before = Some(at.try_into()?);
+                continue;
+            }
+            let year: u16 = key
+                .parse()
+                .map_err(|_| anyhow!("invalid year key [{}]", key))?;
+            let Value::Table(days) = value else {
+                bail!("[{}] should be a table of days", key);
+            };
+            for (day_key, parts) in days {
+                let day: u8 = day_key
+                    .strip_prefix("day")
+                    .unwrap_or(&day_key)
+                    .parse()
+                    .map_err(|_| anyhow!("invalid day key [{}.{}]", year, day_key))?;
+                let Value::Table(parts) = parts else {
+                    bail!("[{}.{}] should be a table of part1/part2", year, day_key);
+                };
+                for (part_str, attempts) in parts {
+                    let part = match part_str.as_str() {
+                        "part1" => Part::One,
+                        "part2" => Part::Two,
+                        _ => bail!("invalid key {} in [{}.{}]", part_str, year, day_key),
+                    };
+                    let Value::Array(attempts) = attempts else {
+                        bail!(
+                            "{}.{}.{} should be a list of attempts",
+                            year,
+                            day_key,
+                            part_str
+                        );
+                    };
+                    let attempts = attempts
+                        .iter()
+                        .map(attempt_from_value)
+                        .collect::<Result<_>>()?;
+                    history.attempts.insert((year, day, part), attempts);
+                }
+            }
+        }
+        Ok(history)
+    }
+
+    /// Loads the history from `path`; a missing file has no attempts yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn attempts(&self, year: u16, day: u8, part: Part) -> &[Attempt] {
+        self.attempts
+            .get(&(year, day, part))
+            .map_or(&[], |a| a.as_slice())
+    }
+
+    /// Refuses an answer that can't be right given earlier attempts, or
+    /// that the site isn't ready to take yet
+    pub fn check(&self, year: u16, day: u8, part: Part, answer: &str, now: u64) -> Result<()> {
+        let attempts = self.attempts(year, day, part);
+        if let Some(right) = attempts.iter().find(|a| a.feedback == Feedback::Right) {
+            bail!("already solved with {}", right.answer);
+        }
+        if let Some(wrong) = attempts
+            .iter()
+            .find(|a| a.answer == answer && a.feedback.is_wrong())
+        {
+            bail!(
+                "{} was already submitted and was {}",
+                answer,
+                wrong.feedback
+            );
+        }
+        if let Ok(n) = answer.parse::<i128>() {
+            for a in attempts {
+                let Ok(bound) = a.answer.parse::<i128>() else {
+                    continue;
+                };
+                match a.feedback {
+                    Feedback::TooHigh if n >= bound => {
+                        bail!("{} is too high, since {} already was", n, bound)
+                    }
+                    Feedback::TooLow if n <= bound => {
+                        bail!("{} is too low, since {} already was", n, bound)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if let Some(not_before) = self.not_before.filter(|&t| t > now) {
+            bail!("the site asked to wait, try again in {}s", not_before - now);
+        }
+        Ok(())
+    }
+
+    /// Notes the site's reply to `answer`, sent at `now`
+    pub fn record(
+        &mut self,
+        year: u16,
+        day: u8,
+        part: Part,
+        answer: &str,
+        reply: &Reply,
+        now: u64,
+    ) {
+        self.attempts
+            .entry((year, day, part))
+            .or_default()
+            .push(Attempt {
+                answer: answer.to_owned(),
+                feedback: reply.feedback,
+                at: now,
+            });
+        self.not_before = reply.wait.map(|wait| now + wait.as_secs());
+    }
+}
+
+fn attempt_from_value(value: &Value) -> Result<Attempt> {
+    let t = value
+        .as_table()
+        .ok_or_else(|| anyhow!("attempt should be a table, got {}", value))?;
+    let get = |key: &str| t.get(key).ok_or_else(|| anyhow!("attempt missing {}", key));
+    let answer = get("answer")?
+        .as_str()
+        .ok_or_else(|| anyhow!("answer should be a string"))?;
+    let feedback = get("feedback")?
+        .as_str()
+        .ok_or_else(|| anyhow!("feedback should be a string"))?;
+    let at = get("at")?
+        .as_integer()
+        .ok_or_else(|| anyhow!("at should be a timestamp"))?;
+    Ok(Attempt {
+        answer: answer.to_owned(),
+        feedback: feedback.parse()?,
+        at: at.try_into()?,
+    })
+}
+
+impl Display for History {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut table = Table::new();
+        if let Some(at) = self.not_before {
+            table.insert("not_before".to_owned(), Value::Integer(at as i64));
+        }
+        for (&(year, day, part), attempts) in &self.attempts {
+            let days = table
+                .entry(year.to_string())
+                .or_insert_with(|| Value::Table(Table::new()));
+            let parts = days
+                .as_table_mut()
+                .unwrap()
+                .entry(format!("day{:02}", day))
+                .or_insert_with(|| Value::Table(Table::new()));
+            let attempts = attempts
+                .iter()
+                .map(|a| {
+                    let mut t = Table::new();
+                    t.insert("answer".to_owned(), Value::String(a.answer.clone()));
+                    t.insert("feedback".to_owned(), Value::String(a.feedback.to_string()));
+                    t.insert("at".to_owned(), Value::Integer(a.at as i64));
+                    Value::Table(t)
+                })
+                .collect();
+            parts
+                .as_table_mut()
+                .unwrap()
+                .insert(part_key(part), Value::Array(attempts));
+        }
+        write!(f, "{}", table)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+
+    use super::*;
+
+    fn reply(feedback: Feedback, wait: Option<u64>) -> Reply {
+        Reply {
+            feedback,
+            wait: wait.map(Duration::from_secs),
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_reply() -> Result<()> {
+        let r = Reply::parse(
+            "<main>\n<article><p>That's the right answer!  You are <em>one gold star</em> closer \
+             to finding the Chief Historian.</p></article>\n</main>",
+        )?;
+        assert_eq!(r.feedback, Feedback::Right);
+        assert!(r
+            .message
+            .starts_with("That's the right answer! You are one gold star closer"));
+
+        let r = Reply::parse(
+            "<article><p>That's not the right answer; your answer is too high.  If you're stuck, \
+             ...  Please wait one minute before trying again. <a href=\"/2024/day/1\">[Return to Day 1]</a></p></article>",
+        )?;
+        assert_eq!(r.feedback, Feedback::TooHigh);
+        assert_eq!(r.wait, Some(Duration::from_secs(60)));
+
+        let r = Reply::parse("<article><p>That's not the right answer; your answer is too low.  Please wait 5 minutes before trying again.</p></article>")?;
+        assert_eq!(
+            (r.feedback, r.wait),
+            (Feedback::TooLow, Some(Duration::from_secs(300)))
+        );
+        let r = Reply::parse("<article><p>That's not the right answer.</p></article>")?;
+        assert_eq!((r.feedback, r.wait), (Feedback::Wrong, None));
+
+        let r = Reply::parse(
+            "<article><p>You gave an answer too recently; you have to wait after submitting an \
+             answer before trying again.  You have 1m 5s left to wait.</p></article>",
+        )?;
+        assert_eq!(r.feedback, Feedback::RateLimited);
+        assert_eq!(r.wait, Some(Duration::from_secs(65)));
+
+        let r = Reply::parse("<article><p>You don't seem to be solving the right level.  Did you already complete it?</p></article>")?;
+        assert_eq!(r.feedback, Feedback::WrongLevel);
+
+        assert!(Reply::parse("<html>Something else</html>").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check() {
+        let mut h = History::default();
+        assert!(h.check(2024, 1, Part::One, "50", 0).is_ok());
+        h.record(
+            2024,
+            1,
+            Part::One,
+            "50",
+            &reply(Feedback::TooHigh, Some(60)),
+            1000,
+        );
+        h.record(
+            2024,
+            1,
+            Part::One,
+            "abc",
+            &reply(Feedback::Wrong, None),
+            2000,
+        );
+        h.record(
+            2024,
+            1,
+            Part::One,
+            "10",
+            &reply(Feedback::TooLow, Some(60)),
+            3000,
+        );
+
+        let e = h.check(2024, 1, Part::One, "abc", 4000).unwrap_err();
+        assert_eq!(e.to_string(), "abc was already submitted and was wrong");
+        let e = h.check(2024, 1, Part::One, "75", 4000).unwrap_err();
+        assert_eq!(e.to_string(), "75 is too high, since 50 already was");
+        let e = h.check(2024, 1, Part::One, "10", 4000).unwrap_err();
+        assert_eq!(e.to_string(), "10 was already submitted and was too low");
+        assert!(h.check(2024, 1, Part::One, "9", 4000).is_err());
+        let e = h.check(2024, 1, Part::One, "30", 3030).unwrap_err();
+        assert_eq!(e.to_string(), "the site asked to wait, try again in 30s");
+        assert!(h.check(2024, 1, Part::One, "30", 4000).is_ok());
+
+        // Other parts and years aren't affected
+        assert!(h.check(2024, 1, Part::Two, "75", 4000).is_ok());
+        assert!(h.check(2023, 1, Part::One, "75", 4000).is_ok());
+
+        h.record(
+            2024,
+            1,
+            Part::One,
+            "30",
+            &reply(Feedback::Right, None),
+            4000,
+        );
+        let e = h.check(2024, 1, Part::One, "31", 5000).unwrap_err();
+        assert_eq!(e.to_string(), "already solved with 30");
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let mut h = History::default();
+        h.record(
+            2024,
+            5,
+            Part::Two,
+            "123",
+            &reply(Feedback::TooLow, Some(60)),
+            1000,
+        );
+        h.record(
+            2024,
+            5,
+            Part::Two,
+            "456",
+            &reply(Feedback::Right, None),
+            2000,
+        );
+        h.record(
+            2023,
+            25,
+            Part::One,
+            "x",
+            &reply(Feedback::RateLimited, Some(30)),
+            3000,
+        );
+        let text = h.to_string();
+        assert!(text.contains("[[2024.day05.part2]]"));
+        assert_eq!(History::parse(&text)?, h);
+        assert_eq!(h.attempts(2024, 5, Part::Two)[0].feedback, Feedback::TooLow);
+        assert!(h.attempts(2024, 5, Part::One).is_empty());
+
+        assert!(History::parse("[2024.day01]\npart3 = []").is_err());
+        assert!(History::parse(
+            "[2024.day01]\npart1 = [{ answer = \"1\", feedback = \"meh\", at = 0 }]"
+        )
+        .is_err());
+        Ok(())
+    }
+}
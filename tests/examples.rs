@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use aoc::examples;
+
+/// Runs every fixture saved by `aoc examples` against its day
+#[test]
+fn test_example_fixtures() -> Result<()> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let fixtures = examples::load_all(&dir)?;
+    let mut failures = Vec::new();
+    for fixture in &fixtures {
+        failures.extend(fixture.check()?);
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} fixtures failed:\n{}",
+        failures.len(),
+        fixtures.len(),
+        failures.join("\n")
+    );
+    Ok(())
+}
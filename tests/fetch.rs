@@ -0,0 +1,95 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use anyhow::Result;
+use tiny_http::{Response, Server};
+
+use aoc::client::Client;
+use aoc::inputs::InputCache;
+
+const SESSION: &str = "s3cret";
+
+/// A stand-in for the puzzle site on a free local port. Day 1 has an input,
+/// later days aren't unlocked, and any other session is refused. Returns the
+/// base URL and a count of requests served.
+fn stub_server() -> (String, Arc<AtomicUsize>) {
+    let server = Server::http("127.0.0.1:0").expect("binding stub server");
+    let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+    let count = requests.clone();
+    thread::spawn(move || {
+        for request in server.incoming_requests() {
+            count.fetch_add(1, Ordering::SeqCst);
+            let cookie = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Cookie"))
+                .map(|h| h.value.to_string());
+            let response = if cookie.as_deref() != Some(&format!("session={}", SESSION)) {
+                Response::from_string(
+                    "Puzzle inputs differ by user.  Please log in to get your puzzle input.",
+                )
+                .with_status_code(400)
+            } else if request.url() == "/2024/day/1/input" {
+                Response::from_string("3   4\n4   3\n")
+            } else {
+                Response::from_string("404 Not Found").with_status_code(404)
+            };
+            _ = request.respond(response);
+        }
+    });
+    (base_url, requests)
+}
+
+/// A fresh, empty directory for one test's inputs
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aoc-{}-{}", name, std::process::id()));
+    _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_fetch_and_cache() -> Result<()> {
+    let (base_url, requests) = stub_server();
+    let dir = temp_dir("fetch");
+    let client = Client::new(&base_url, Some(SESSION));
+    let cache = InputCache::new(&dir, 2024, &client);
+
+    let input = cache.load(1)?;
+    assert_eq!(input.text(), "3   4\n4   3");
+    assert_eq!(cache.path(1), dir.join("2024/01.txt"));
+    assert_eq!(fs::read_to_string(cache.path(1))?, "3   4\n4   3\n");
+
+    // Served from disk the second time
+    cache.load(1)?;
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_fetch_errors() -> Result<()> {
+    let (base_url, requests) = stub_server();
+    let dir = temp_dir("fetch-errors");
+
+    let client = Client::new(&base_url, Some(SESSION));
+    let e = InputCache::new(&dir, 2024, &client).load(25).unwrap_err();
+    assert_eq!(e.to_string(), "day 25 of 2024 is not unlocked yet");
+    // Nothing cached, so it's tried again once unlocked
+    assert!(!dir.join("2024/25.txt").exists());
+
+    let client = Client::new(&base_url, Some("stale"));
+    let e = InputCache::new(&dir, 2024, &client).load(1).unwrap_err();
+    assert!(e
+        .to_string()
+        .starts_with("session token was rejected (HTTP 400)"));
+
+    let client = Client::new(&base_url, None);
+    let e = InputCache::new(&dir, 2024, &client).load(1).unwrap_err();
+    assert!(e.to_string().starts_with("no session token"));
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anyhow::{anyhow, Result};
+use indoc::indoc;
+
+use aoc::common::{Dir, Input, Pos};
+
+#[test]
+fn test_dispatch_by_day() -> Result<()> {
+    let solver = aoc::registry()
+        .iter()
+        .find(|s| s.day() == 1)
+        .ok_or_else(|| anyhow!("day 1 missing"))?;
+    let input = Input::new(indoc! {"
+        3   4
+        4   3
+        2   5
+        1   3
+        3   9
+        3   3
+    "});
+    let params = solver.default_params();
+    assert_eq!(solver.part1(&input, &params)?, "11");
+    assert_eq!(solver.part2(&input, &params)?, "31");
+    Ok(())
+}
+
+#[test]
+fn test_day_modules_are_public() -> Result<()> {
+    let input = Input::new("0 1 10 99 999");
+    assert_eq!(aoc::day11::part2(&input, 1)?, "7");
+    Ok(())
+}
+
+#[test]
+fn test_pos_helpers() {
+    let bounds: Pos<usize> = Pos { x: 3, y: 3 };
+    let p = Pos { x: 0, y: 0 };
+    assert_eq!(p.go_bounded(Dir::Up, &bounds), None);
+    assert_eq!(p.go_bounded(Dir::Right, &bounds), Some(Pos { x: 1, y: 0 }));
+    assert_eq!(bounds.generator().count(), 9);
+}
@@ -0,0 +1,111 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use tiny_http::{Response, Server};
+
+use aoc::client::Client;
+use aoc::solver::Part;
+use aoc::submit::{Feedback, History};
+
+fn page(message: &str) -> String {
+    format!(
+        "<html><body><main>\n<article><p>{}</p></article>\n</main></body></html>",
+        message
+    )
+}
+
+/// A stand-in for the puzzle site whose day 1 part 1 answer is 42. Returns
+/// the base URL and the form bodies it was sent.
+fn stub_server() -> (String, Arc<Mutex<Vec<String>>>) {
+    let server = Server::http("127.0.0.1:0").expect("binding stub server");
+    let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let seen = bodies.clone();
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            _ = request.as_reader().read_to_string(&mut body);
+            let answer: Option<i64> = body
+                .split('&')
+                .find_map(|kv| kv.strip_prefix("answer="))
+                .and_then(|a| a.parse().ok());
+            let message = match answer {
+                _ if request.url() != "/2024/day/1/answer" => {
+                    _ = request.respond(Response::from_string("404 Not Found").with_status_code(404));
+                    continue;
+                }
+                _ if seen.lock().unwrap().len() >= 3 => "You gave an answer too recently; you have \
+                    to wait after submitting an answer before trying again.  You have 45s left to wait.",
+                Some(42) => "That's the right answer!  You are <em>one gold star</em> closer.",
+                Some(n) if n > 42 => "That's not the right answer; your answer is too high.  \
+                    Please wait one minute before trying again.",
+                Some(_) => "That's not the right answer; your answer is too low.",
+                None => "That's not the right answer.",
+            };
+            seen.lock().unwrap().push(body);
+            _ = request.respond(Response::from_string(page(message)));
+        }
+    });
+    (base_url, bodies)
+}
+
+#[test]
+fn test_submit() -> Result<()> {
+    let (base_url, bodies) = stub_server();
+    let client = Client::new(&base_url, Some("s3cret"));
+
+    let reply = client.submit(2024, 1, Part::One, "50")?;
+    assert_eq!(reply.feedback, Feedback::TooHigh);
+    assert_eq!(reply.wait, Some(Duration::from_secs(60)));
+    assert_eq!(
+        reply.message,
+        "That's not the right answer; your answer is too high. Please wait one minute before trying again."
+    );
+    assert_eq!(bodies.lock().unwrap()[0], "level=1&answer=50");
+
+    assert_eq!(
+        client.submit(2024, 1, Part::One, "7")?.feedback,
+        Feedback::TooLow
+    );
+    let reply = client.submit(2024, 1, Part::Two, "42")?;
+    assert_eq!(reply.feedback, Feedback::Right);
+    assert_eq!(bodies.lock().unwrap()[2], "level=2&answer=42");
+
+    let reply = client.submit(2024, 1, Part::One, "42")?;
+    assert_eq!(reply.feedback, Feedback::RateLimited);
+    assert_eq!(reply.wait, Some(Duration::from_secs(45)));
+
+    let e = client.submit(2024, 25, Part::One, "1").unwrap_err();
+    assert_eq!(e.to_string(), "day 25 of 2024 is not unlocked yet");
+    Ok(())
+}
+
+#[test]
+fn test_history_guards_submissions() -> Result<()> {
+    let (base_url, bodies) = stub_server();
+    let client = Client::new(&base_url, Some("s3cret"));
+    let mut history = History::default();
+    let mut now = 1_000;
+
+    let attempt = |history: &mut History, answer: &str, now: u64| -> Result<Feedback> {
+        history.check(2024, 1, Part::One, answer, now)?;
+        let reply = client.submit(2024, 1, Part::One, answer)?;
+        history.record(2024, 1, Part::One, answer, &reply, now);
+        Ok(reply.feedback)
+    };
+    assert_eq!(attempt(&mut history, "50", now)?, Feedback::TooHigh);
+    // Still within the minute the site asked for
+    assert!(attempt(&mut history, "45", now + 30).is_err());
+    now += 60;
+    // Ruled out by the earlier attempt without asking the site
+    assert!(attempt(&mut history, "50", now).is_err());
+    assert!(attempt(&mut history, "60", now).is_err());
+    assert_eq!(attempt(&mut history, "42", now)?, Feedback::Right);
+    assert!(attempt(&mut history, "42", now).is_err());
+
+    assert_eq!(bodies.lock().unwrap().len(), 2);
+    assert_eq!(History::parse(&history.to_string())?, history);
+    Ok(())
+}